@@ -0,0 +1,50 @@
+//! Measures lexer throughput on a large generated source.
+//!
+//! Run with `cargo bench --bench lexer`. To compare with the `Vec<char>` based
+//! lexer that came before the streaming one, run the `tokenize` case on the
+//! commit before it:
+//!
+//! ```text
+//! git worktree add ../shaba-baseline 20cc138^
+//! mkdir ../shaba-baseline/benches
+//! grep -v streaming benches/lexer.rs > ../shaba-baseline/benches/lexer.rs
+//! printf '\n[[bench]]\nname = "lexer"\nharness = false\n' >> ../shaba-baseline/Cargo.toml
+//! (cd ../shaba-baseline && cargo bench --bench lexer)
+//! git worktree remove --force ../shaba-baseline
+//! ```
+
+use shaba_lang::lexer::lib::Lexer;
+use std::time::{Duration, Instant};
+
+const EXAMPLE: &str = include_str!("../examples/example.shab");
+const COPIES: usize = 2_000;
+const ITERATIONS: usize = 10;
+
+fn main() {
+    let source = EXAMPLE.repeat(COPIES);
+    let megabytes = source.len() as f64 / 1_000_000.0;
+    println!("lexing {megabytes:.1} MB, best of {ITERATIONS} runs");
+
+    let (elapsed, tokens) = best_of(|| Lexer::new(&source).tokenize().unwrap().len());
+    report("tokenize", elapsed, tokens, megabytes);
+
+    let streaming = || Lexer::new(&source).map(Result::unwrap).count();
+    let (elapsed, tokens) = best_of(streaming);
+    report("streaming", elapsed, tokens, megabytes);
+}
+
+fn best_of(mut run: impl FnMut() -> usize) -> (Duration, usize) {
+    (0..ITERATIONS)
+        .map(|_| {
+            let start = Instant::now();
+            let tokens = std::hint::black_box(run());
+            (start.elapsed(), tokens)
+        })
+        .min()
+        .unwrap()
+}
+
+fn report(name: &str, elapsed: Duration, tokens: usize, megabytes: f64) {
+    let throughput = megabytes / elapsed.as_secs_f64();
+    println!("{name:>10}: {tokens} tokens in {elapsed:.2?} ({throughput:.1} MB/s)");
+}
@@ -15,3 +15,24 @@ fn get_file_flag() -> Option<String> {
     let file_name = args.get(index + 1)?;
     Some(file_name.clone())
 }
+
+/// Whether the opt-in lint for mixed-script and confusable identifiers was
+/// requested with `--lint-identifiers`.
+pub fn is_identifier_lint_enabled() -> bool {
+    has_flag("--lint-identifiers")
+}
+
+/// Whether printing the tokens of the source was requested with
+/// `--dump-tokens`.
+pub fn is_token_dump_enabled() -> bool {
+    has_flag("--dump-tokens")
+}
+
+/// Whether printing the parsed program was requested with `--dump-ast`.
+pub fn is_ast_dump_enabled() -> bool {
+    has_flag("--dump-ast")
+}
+
+fn has_flag(flag: &str) -> bool {
+    env::args().any(|arg| arg == flag)
+}
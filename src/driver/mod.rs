@@ -1,4 +1,6 @@
 pub mod error;
 mod lib;
 
-pub use lib::read_source;
+pub use lib::{
+    is_ast_dump_enabled, is_identifier_lint_enabled, is_token_dump_enabled, read_source,
+};
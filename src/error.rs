@@ -1,6 +1,6 @@
 use crate::driver::error::DriverError;
 use crate::lexer::error::LexerError;
-use crate::parser::error::ParserError;
+use crate::parser::error::{ParserError, SyntaxError};
 
 #[derive(Debug)]
 pub enum ShabaCompilerError {
@@ -21,8 +21,11 @@ impl From<LexerError> for ShabaCompilerError {
     }
 }
 
-impl From<ParserError> for ShabaCompilerError {
-    fn from(_e: ParserError) -> ShabaCompilerError {
-        ShabaCompilerError::Parser
+impl From<ParserError<'_>> for ShabaCompilerError {
+    fn from(e: ParserError) -> ShabaCompilerError {
+        match e.error() {
+            SyntaxError::Lexer(_) => ShabaCompilerError::Lexer,
+            _ => ShabaCompilerError::Parser,
+        }
     }
 }
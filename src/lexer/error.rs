@@ -14,10 +14,35 @@ impl LexerError {
             location: start..end,
         }
     }
+
+    pub fn error(&self) -> &TokenizeError {
+        &self.error
+    }
+
+    pub fn location(&self) -> &Range<SourceLocation> {
+        &self.location
+    }
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TokenizeError {
     UnterminatedString,
+    UnterminatedMultilineString,
+    UnterminatedRawString,
+    UnterminatedCharLiteral,
+    EmptyCharLiteral,
+    MultipleCharsInCharLiteral,
+    MultilineStringMustStartOnNewLine,
+    InsufficientIndentation,
+    UnterminatedBlockComment,
+    InvalidClosureArg,
+    MissingAttributeName,
+    UnknownEscape(char),
+    InvalidUnicodeEscape,
     UnknownLexme(char),
+    MalformedFloat(String),
+    IntegerLiteralOverflow(String),
+    InvalidDigit { digit: char, radix: u32 },
+    MissingDigits,
+    InvalidNumberSuffix(String),
 }
@@ -0,0 +1,89 @@
+use std::{borrow::Cow, collections::HashSet, fmt, ops::Deref, rc::Rc};
+
+/// The text of an identifier, attribute or string token. It borrows from the
+/// source, unless normalization or escapes make it differ from the source, in
+/// which case it is interned.
+#[derive(Clone)]
+pub enum Text<'a> {
+    Borrowed(&'a str),
+    Interned(Rc<str>),
+}
+
+impl Deref for Text<'_> {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        match self {
+            Text::Borrowed(text) => text,
+            Text::Interned(text) => text,
+        }
+    }
+}
+
+impl AsRef<str> for Text<'_> {
+    fn as_ref(&self) -> &str {
+        self
+    }
+}
+
+impl<'a> From<&'a str> for Text<'a> {
+    fn from(text: &'a str) -> Self {
+        Text::Borrowed(text)
+    }
+}
+
+/// Texts are equal when their contents are, however they are stored.
+impl PartialEq for Text<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        **self == **other
+    }
+}
+
+impl Eq for Text<'_> {}
+
+impl PartialEq<str> for Text<'_> {
+    fn eq(&self, other: &str) -> bool {
+        &**self == other
+    }
+}
+
+impl PartialEq<&str> for Text<'_> {
+    fn eq(&self, other: &&str) -> bool {
+        &**self == *other
+    }
+}
+
+impl fmt::Debug for Text<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
+
+impl fmt::Display for Text<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&**self, f)
+    }
+}
+
+/// Stores one copy of every text that the lexer can't borrow from the source,
+/// so that repeated normalized identifiers and escaped strings share it.
+#[derive(Default)]
+pub struct Interner {
+    texts: HashSet<Rc<str>>,
+}
+
+impl Interner {
+    pub fn intern<'a>(&mut self, text: Cow<'a, str>) -> Text<'a> {
+        let text = match text {
+            Cow::Borrowed(text) => return Text::Borrowed(text),
+            Cow::Owned(text) => text,
+        };
+        if let Some(interned) = self.texts.get(text.as_str()) {
+            return Text::Interned(interned.clone());
+        }
+
+        let interned: Rc<str> = text.into();
+        self.texts.insert(interned.clone());
+        Text::Interned(interned)
+    }
+}
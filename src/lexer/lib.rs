@@ -1,50 +1,287 @@
 use super::{
     error::{LexerError, TokenizeError},
+    interner::Interner,
     source::Source,
-    token::{Keyword, Literal, TokenKind},
+    source_map::FileId,
+    token::{Keyword, Literal, NumberSuffix, SourceLocation, TokenKind, Trivia, MAX_OPERATOR_LEN},
 };
 use crate::lexer::token::Token;
+use std::borrow::Cow;
+use unicode_normalization::{is_nfc, UnicodeNormalization};
+use unicode_xid::UnicodeXID;
 
-pub struct Lexer {
-    source: Source,
+/// Lexes a source lazily: tokens are read one at a time by iterating over the
+/// lexer, and borrow their text from the source where possible. Text that
+/// differs from the source is interned.
+pub struct Lexer<'a> {
+    source: Source<'a>,
+    token_start: SourceLocation,
+    /// Where the text of the current token starts in the source, or where its
+    /// leading trivia starts until that is read.
+    token_start_index: usize,
+    /// The trivia read in front of the current token in lossless mode.
+    leading_trivia: Vec<Trivia<'a>>,
+    /// Whether errors are collected into `errors` instead of ending lexing.
+    is_recovering: bool,
+    errors: Vec<LexerError>,
+    previous_token_end: SourceLocation,
+    /// Whether whitespace or comments precede the current token.
+    is_after_whitespace: bool,
+    /// Whether tokens keep their source text and trivia.
+    is_lossless: bool,
+    is_eof_lexed: bool,
+    /// Whether an error ended lexing.
+    is_failed: bool,
+    /// Interpolations the lexer is currently inside of, innermost last.
+    interpolations: Vec<Interpolation<'a>>,
+    string_state: Option<StringState<'a>>,
+    interner: Interner,
 }
 
-impl Lexer {
-    pub fn new(source: &str) -> Lexer {
+/// The delimiters of a string literal, which decide how its contents are read.
+#[derive(Clone)]
+struct StringDelimiter<'a> {
+    /// Where the opening delimiter is.
+    start: SourceLocation,
+    /// How many `#` surround a raw string. Raw strings have no escapes.
+    hashes: usize,
+    /// The indentation stripped from every line of a `"""` multi-line string,
+    /// taken from the line of its closing delimiter.
+    indentation: Option<&'a str>,
+}
+
+impl StringDelimiter<'_> {
+    fn is_raw(&self) -> bool {
+        self.hashes > 0
+    }
+
+    fn unterminated_error(&self) -> TokenizeError {
+        if self.indentation.is_some() {
+            TokenizeError::UnterminatedMultilineString
+        } else if self.is_raw() {
+            TokenizeError::UnterminatedRawString
+        } else {
+            TokenizeError::UnterminatedString
+        }
+    }
+}
+
+/// An open `\(...)` inside of a string literal.
+struct Interpolation<'a> {
+    /// The string literal the interpolation is in.
+    delimiter: StringDelimiter<'a>,
+    /// How many unclosed `(` the interpolated expression contains, so that
+    /// only the matching `)` ends the interpolation.
+    paren_depth: usize,
+}
+
+/// Marks that the lexer stopped at a boundary inside of an interpolated string
+/// and must continue the string instead of lexing a regular token.
+enum StringState<'a> {
+    /// A string segment ended right before `\(`.
+    AtInterpolation(StringDelimiter<'a>),
+    /// An interpolation was closed and the rest of the string follows.
+    AfterInterpolation(StringDelimiter<'a>),
+}
+
+impl<'a> Lexer<'a> {
+    pub fn new(source: &'a str) -> Lexer<'a> {
+        Self::with_file(FileId::default(), source)
+    }
+
+    /// Creates a lexer for a file added to a `SourceMap`, so that every
+    /// location it produces points into that file.
+    pub fn with_file(file: FileId, source: &'a str) -> Lexer<'a> {
+        let source = Source::new(file, source);
+        let token_start = source.location();
         Self {
-            source: Source::new(source),
+            source,
+            previous_token_end: token_start.clone(),
+            token_start,
+            token_start_index: 0,
+            leading_trivia: Vec::new(),
+            is_recovering: false,
+            errors: Vec::new(),
+            is_after_whitespace: false,
+            is_lossless: false,
+            is_eof_lexed: false,
+            is_failed: false,
+            interpolations: Vec::new(),
+            string_state: None,
+            interner: Interner::default(),
         }
     }
 
-    pub fn tokenize(&mut self) -> Result<Vec<Token>, LexerError> {
-        let mut tokens: Vec<Token> = Vec::<Token>::new();
+    /// Keeps all whitespace and comments as trivia on the tokens, so that
+    /// concatenating the `source_text` of every token reproduces the source
+    /// exactly. The last token is a `TokenKind::Eof` holding the trivia at the
+    /// end of the source.
+    pub fn lossless(self) -> Self {
+        Self {
+            is_lossless: true,
+            ..self
+        }
+    }
 
-        while let Some(token) = self.next_token()? {
-            tokens.push(token);
+    /// Lexes the whole source even if it contains errors. Invalid input is
+    /// lexed as `TokenKind::Error` tokens and the errors are collected, to be
+    /// taken with `take_errors`.
+    pub fn recovering(self) -> Self {
+        Self {
+            is_recovering: true,
+            ..self
         }
+    }
+
+    /// The errors collected while recovering since the last call.
+    pub fn take_errors(&mut self) -> Vec<LexerError> {
+        std::mem::take(&mut self.errors)
+    }
+
+    pub fn tokenize(&mut self) -> Result<Vec<Token<'a>>, LexerError> {
+        let tokens = self.collect::<Result<Vec<Token>, LexerError>>()?;
 
         assert!(self.source.is_eof());
         Ok(tokens)
     }
 
-    fn next_token(&mut self) -> Result<Option<Token>, LexerError> {
-        self.source.advance_to_next_token();
+    /// Tokenizes the whole source in lossless mode, see `lossless`.
+    pub fn tokenize_lossless(&mut self) -> Result<Vec<Token<'a>>, LexerError> {
+        self.is_lossless = true;
+        self.tokenize()
+    }
 
-        let start = self.source.location();
-        let next_token_kind = self.next_token_kind();
+    /// Tokenizes the whole source in recovering mode, see `recovering`.
+    pub fn tokenize_recovering(&mut self) -> (Vec<Token<'a>>, Vec<LexerError>) {
+        self.is_recovering = true;
+        // Errors are recovered from, so every item is a token.
+        let tokens: Vec<Token> = self.flatten().collect();
+
+        assert!(self.source.is_eof());
+        (tokens, self.take_errors())
+    }
+
+    /// Records `error` and returns an error token covering the invalid input.
+    ///
+    /// Lexing continues right after the invalid input. Since strings cannot
+    /// span lines, an unterminated string ends at the end of its line.
+    fn recover(&mut self, error: LexerError) -> Token<'a> {
+        if self.source.is_eof() {
+            self.interpolations.clear();
+            self.string_state = None;
+        } else if self.source.location() == self.token_start {
+            self.source.next();
+        }
+
+        let location = error.location().clone();
+        self.errors.push(error);
+        let token = Token::new(TokenKind::Error, location.start, location.end);
+        if !self.is_lossless {
+            return token;
+        }
+
+        // The skipped input is the token's text, and the trivia after it is
+        // left to the next token.
+        let leading_trivia = std::mem::take(&mut self.leading_trivia);
+        let text = self.source.text_from(self.token_start_index);
+        token.with_trivia(leading_trivia, text, Vec::new())
+    }
+
+    /// Records an error the lexer can carry on from when recovering, and fails
+    /// with it otherwise.
+    fn report(&mut self, error: LexerError) -> Result<(), LexerError> {
+        if !self.is_recovering {
+            return Err(error);
+        }
+        self.errors.push(error);
+        Ok(())
+    }
+
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, LexerError> {
+        self.token_start_index = self.source.index();
+        self.leading_trivia = self.read_leading_trivia()?;
+
+        self.token_start = self.source.location();
+        self.token_start_index = self.source.index();
+        self.is_after_whitespace = self.token_start != self.previous_token_end;
+        let next_token_kind = match self.string_state.take() {
+            Some(StringState::AtInterpolation(delimiter)) => {
+                Some(self.read_interpolation_start(delimiter))
+            }
+            Some(StringState::AfterInterpolation(delimiter)) => {
+                Some(self.read_str_segment(delimiter, false)?)
+            }
+            None => self.next_token_kind()?,
+        };
         let end = self.source.location();
+        self.previous_token_end = end.clone();
 
-        match next_token_kind {
-            Ok(Some(token_kind)) => Ok(Some(Token::new(token_kind, start, end))),
-            Ok(None) => Ok(None),
-            Err(e) => Err(LexerError::new(e, start, end)),
+        let Some(kind) = next_token_kind else {
+            let leading_trivia = std::mem::take(&mut self.leading_trivia);
+            return Ok(self.lex_eof(leading_trivia));
+        };
+        let token = Token::new(kind, self.token_start.clone(), end);
+        if !self.is_lossless {
+            return Ok(Some(token));
+        }
+
+        let text = self.source.text_from(self.token_start_index);
+        let trailing_trivia = match self.string_state {
+            Some(_) => Vec::new(),
+            None => self.source.read_trivia(true)?,
+        };
+        let leading_trivia = std::mem::take(&mut self.leading_trivia);
+        Ok(Some(token.with_trivia(
+            leading_trivia,
+            text,
+            trailing_trivia,
+        )))
+    }
+
+    fn read_leading_trivia(&mut self) -> Result<Vec<Trivia<'a>>, LexerError> {
+        if self.string_state.is_some() {
+            return Ok(Vec::new());
+        }
+        // An interpolation in a single-line string can't continue on the next
+        // line, so its trivia ends in front of the newline.
+        let is_in_single_line_interpolation = self.is_in_single_line_interpolation();
+        if self.is_lossless {
+            return self.source.read_trivia(is_in_single_line_interpolation);
         }
+        if is_in_single_line_interpolation {
+            self.source.read_trivia(true)?;
+        } else {
+            self.source.advance_to_next_token()?;
+        }
+        Ok(Vec::new())
+    }
+
+    /// In lossless mode the end of the source is lexed as a token once, so the
+    /// trivia in front of it is kept.
+    fn lex_eof(&mut self, leading_trivia: Vec<Trivia<'a>>) -> Option<Token<'a>> {
+        if !self.is_lossless || self.is_eof_lexed {
+            return None;
+        }
+        self.is_eof_lexed = true;
+
+        let location = self.source.location();
+        let token = Token::new(TokenKind::Eof, location.clone(), location);
+        Some(token.with_trivia(leading_trivia, "", Vec::new()))
+    }
+
+    /// Creates an error spanning from the start of the current token up to the
+    /// current position in the source.
+    fn error(&self, error: TokenizeError) -> LexerError {
+        LexerError::new(error, self.token_start.clone(), self.source.location())
     }
 
-    fn next_token_kind(&mut self) -> Result<Option<TokenKind>, TokenizeError> {
-        let single_char_token = self.read_single_char_token();
-        if single_char_token.is_some() {
-            return Ok(single_char_token);
+    fn next_token_kind(&mut self) -> Result<Option<TokenKind<'a>>, LexerError> {
+        if let Some(doc_comment) = self.read_doc_comment()? {
+            return Ok(Some(doc_comment));
+        }
+
+        if let Some(token_kind) = self.read_operator() {
+            return Ok(Some(self.track_interpolation(token_kind)));
         }
 
         let literal = self.read_literal()?;
@@ -52,112 +289,664 @@ impl Lexer {
             return Ok(literal);
         }
 
+        let closure_arg = self.read_closure_arg()?;
+        if closure_arg.is_some() {
+            return Ok(closure_arg);
+        }
+
+        let attribute = self.read_attribute()?;
+        if attribute.is_some() {
+            return Ok(attribute);
+        }
+
         let lexme = self.read_lexme();
         if lexme.is_some() {
             return Ok(lexme);
         }
 
+        if self.source.peek() == Some('\n') {
+            if let Some(delimiter) = self.close_single_line_interpolations() {
+                let error = delimiter.unterminated_error();
+                return Err(LexerError::new(
+                    error,
+                    delimiter.start,
+                    self.source.location(),
+                ));
+            }
+        }
+
         let Some(lexme) = self.source.next() else {
+            if let Some(interpolation) = self.interpolations.last() {
+                let delimiter = &interpolation.delimiter;
+                let error = delimiter.unterminated_error();
+                let start = delimiter.start.clone();
+                return Err(LexerError::new(error, start, self.source.location()));
+            }
             return Ok(None);
         };
-        Err(TokenizeError::UnknownLexme(lexme))
+        Err(self.error(TokenizeError::UnknownLexme(lexme)))
+    }
+
+    /// Closes the interpolations of single-line strings at the end of their
+    /// line, returning the delimiter of the outermost string they were in.
+    fn close_single_line_interpolations(&mut self) -> Option<StringDelimiter<'a>> {
+        let mut delimiter = None;
+        while self.is_in_single_line_interpolation() {
+            delimiter = self
+                .interpolations
+                .pop()
+                .map(|interpolation| interpolation.delimiter);
+        }
+        delimiter
     }
 
-    fn read_lexme(&mut self) -> Option<TokenKind> {
-        let ch = self.source.next_if(is_start_of_identifier)?;
+    fn is_in_single_line_interpolation(&self) -> bool {
+        self.interpolations
+            .last()
+            .is_some_and(|interpolation| interpolation.delimiter.indentation.is_none())
+    }
 
-        let mut chars: Vec<char> = vec![ch];
-        while let Some(ch) = self.source.next_if(is_identifier) {
-            chars.push(ch);
+    fn read_doc_comment(&mut self) -> Result<Option<TokenKind<'a>>, LexerError> {
+        if !self.source.is_at_start_of_doc_comment() {
+            return Ok(None);
         }
-        let lexme = &String::from_iter(chars);
 
-        if let Some(literal) = Literal::as_bool(lexme) {
-            return TokenKind::Literal(literal).into();
+        if self.source.is_next("///") {
+            self.source.advance_by(3);
+            let text = self.source.take_while(|ch| ch != '\n').unwrap_or_default();
+            return Ok(Some(TokenKind::DocComment(text)));
         }
 
-        if let Some(keyword) = Keyword::from_str(lexme) {
-            return TokenKind::Keyword(keyword).into();
+        let text = self.source.read_block_comment()?;
+        let text = text.strip_prefix('*').unwrap_or(text);
+        Ok(Some(TokenKind::DocComment(text)))
+    }
+
+    /// Reads an anonymous closure argument such as `$0`.
+    fn read_closure_arg(&mut self) -> Result<Option<TokenKind<'a>>, LexerError> {
+        if self.source.next_if(|ch| ch == '$').is_none() {
+            return Ok(None);
+        }
+
+        let Some(digits) = self.source.take_while(|ch| ch.is_ascii_digit()) else {
+            return Err(self.error(TokenizeError::InvalidClosureArg));
+        };
+        if self.source.peek().is_some_and(is_identifier) {
+            self.source.advance_while(is_identifier);
+            return Err(self.error(TokenizeError::InvalidClosureArg));
+        }
+        let index = digits
+            .parse::<u32>()
+            .map_err(|_| self.error(TokenizeError::IntegerLiteralOverflow(digits.to_string())))?;
+        Ok(Some(TokenKind::ClosureArg(index)))
+    }
+
+    /// Reads the `@inline` part of an attribute. Its arguments, as in
+    /// `@deprecated("use g")`, are lexed as regular tokens.
+    fn read_attribute(&mut self) -> Result<Option<TokenKind<'a>>, LexerError> {
+        if self.source.next_if(|ch| ch == '@').is_none() {
+            return Ok(None);
         }
 
-        TokenKind::Identifier(lexme.to_string()).into()
+        let Some(name) = self
+            .source
+            .take_while_from(is_start_of_identifier, is_identifier)
+        else {
+            return Err(self.error(TokenizeError::MissingAttributeName));
+        };
+        let name = self.interner.intern(normalize_identifier(name));
+        Ok(Some(TokenKind::Attribute(name)))
     }
 
-    // TODO: this fn name makes no sense anymore
-    // Find a better way to do this
-    // The token should be responsible for trying to map a string into Option<TokenKind>
-    // This should only read stuff and present it to that fn
-    fn read_single_char_token(&mut self) -> Option<TokenKind> {
-        let token_kind = self.source.next_map(TokenKind::from_char)?;
+    fn read_lexme(&mut self) -> Option<TokenKind<'a>> {
+        let lexme = self
+            .source
+            .take_while_from(is_start_of_identifier, is_identifier)?;
+        let lexme = normalize_identifier(lexme);
 
-        let is_maybe_double_char_token = matches!(token_kind, TokenKind::Eq)
-            || matches!(token_kind, TokenKind::GreaterThan)
-            || matches!(token_kind, TokenKind::LessThan)
-            || matches!(token_kind, TokenKind::Negate);
+        if let Some(literal) = Literal::as_bool(&lexme) {
+            return TokenKind::Literal(literal).into();
+        }
 
-        if !is_maybe_double_char_token {
-            return Some(token_kind);
+        if let Some(keyword) = Keyword::from_str(&lexme) {
+            return TokenKind::Keyword(keyword).into();
         }
 
-        if self.source.next_if(|x| x == '=').is_some() {
-            match token_kind {
-                TokenKind::Eq => Some(TokenKind::EqEq),
-                TokenKind::GreaterThan => Some(TokenKind::GreaterThanEq),
-                TokenKind::LessThan => Some(TokenKind::LessThanEq),
-                TokenKind::Negate => Some(TokenKind::NotEq),
-                _ => Some(token_kind),
-            }
-        } else {
-            Some(token_kind)
+        TokenKind::Identifier(self.interner.intern(lexme)).into()
+    }
+
+    /// Reads the longest operator or punctuation the upcoming chars spell, so
+    /// `<=` is never lexed as `<` followed by `=`.
+    ///
+    /// `?.` is the exception: it is only an optional chain in `a?.b`, where the
+    /// `?` is attached to the value before it. In `a ? .b : c` the `?` is a
+    /// ternary and `.b` an implicit member expression.
+    fn read_operator(&mut self) -> Option<TokenKind<'a>> {
+        let upcoming = self.source.rest();
+        let (len, token_kind) = (1..=MAX_OPERATOR_LEN).rev().find_map(|len| {
+            let operator = upcoming.get(..len)?;
+            TokenKind::from_operator(operator).map(|token_kind| (len, token_kind))
+        })?;
+
+        if token_kind == TokenKind::OptionalChain && self.is_after_whitespace {
+            self.source.next();
+            return Some(TokenKind::QuestionMark);
         }
+
+        self.source.advance_by(len);
+        Some(token_kind)
     }
 
-    fn read_literal(&mut self) -> Result<Option<TokenKind>, TokenizeError> {
-        if let Some(str) = self.read_str()? {
-            let token_kind = TokenKind::Literal(Literal::String(str));
+    fn read_literal(&mut self) -> Result<Option<TokenKind<'a>>, LexerError> {
+        if let Some(token_kind) = self.read_str()? {
             return Ok(Some(token_kind));
         }
-        if let Some(num) = self.read_number() {
+        if let Some(ch) = self.read_char()? {
+            return Ok(Some(TokenKind::Literal(ch)));
+        }
+        if let Some(num) = self.read_number()? {
             let token_kind = TokenKind::Literal(num);
             return Ok(Some(token_kind));
         }
         Ok(None)
     }
 
-    fn read_str(&mut self) -> Result<Option<String>, TokenizeError> {
-        if self.source.next_if(|ch| ch == '"').is_none() {
+    /// Reads a character literal such as `'a'`, `'\n'` or `'\u{2764}'`, which
+    /// must hold exactly one char.
+    fn read_char(&mut self) -> Result<Option<Literal<'a>>, LexerError> {
+        if self.source.next_if(|ch| ch == '\'').is_none() {
             return Ok(None);
         }
 
-        let str = self
-            .source
-            .take_while(|ch| ch != '"')
-            .unwrap_or(String::from(""));
+        let mut chars: Vec<char> = Vec::new();
+        loop {
+            let start = self.source.location();
+            match self.source.peek() {
+                Some('\'') => {
+                    self.source.next();
+                    break;
+                }
+                Some('\\') if self.source.peek_next().is_some_and(|ch| ch != '\n') => {
+                    self.source.next();
+                    chars.push(self.read_escape(start)?);
+                }
+                Some(ch) if ch != '\n' => {
+                    self.source.next();
+                    chars.push(ch);
+                }
+                _ => return Err(self.error(TokenizeError::UnterminatedCharLiteral)),
+            }
+        }
+
+        match chars[..] {
+            [ch] => Ok(Some(Literal::Char(ch))),
+            [] => Err(self.error(TokenizeError::EmptyCharLiteral)),
+            _ => Err(self.error(TokenizeError::MultipleCharsInCharLiteral)),
+        }
+    }
+
+    /// Reads a string literal, or the first segment of an interpolated string.
+    ///
+    /// `"\(name) is \(age)"` is lexed as `StringPart("")`,
+    /// `InterpolationStart`, `Identifier("name")`, `InterpolationEnd`,
+    /// `StringPart(" is ")`, `InterpolationStart`, `Identifier("age")`,
+    /// `InterpolationEnd`, `StringPart("")`. An interpolated string therefore
+    /// always starts and ends with a `StringPart`, and strings without
+    /// interpolations are plain `Literal::String`s.
+    ///
+    /// Strings wrapped in `#` (as in `#"say "hi""#`) are raw and take their
+    /// contents verbatim. Only strings opened with `"""` may span multiple
+    /// lines: the contents start on the line after the opening delimiter, and
+    /// the indentation of the closing delimiter is removed from every line.
+    fn read_str(&mut self) -> Result<Option<TokenKind<'a>>, LexerError> {
+        let start = self.source.location();
+        let hashes = self.source.count_while(|ch| ch == '#');
+        if self.source.peek_nth(hashes) != Some('"') {
+            return Ok(None);
+        }
+        self.source.advance_by(hashes + 1);
+
+        let mut delimiter = StringDelimiter {
+            start,
+            hashes,
+            indentation: None,
+        };
+
+        if self.source.is_next("\"\"") {
+            self.source.advance_by(2);
+            if self.source.next_if(|ch| ch == '\n').is_none() {
+                let error = TokenizeError::MultilineStringMustStartOnNewLine;
+                return Err(self.error(error));
+            }
+
+            let Some(indentation) = self.find_closing_indentation(hashes) else {
+                self.source.advance_while(|_| true);
+                return Err(self.error(TokenizeError::UnterminatedMultilineString));
+            };
+            delimiter.indentation = Some(indentation);
+        }
+
+        self.read_str_segment(delimiter, true).map(Some)
+    }
+
+    /// Reads string contents up to the closing delimiter or the next `\(`.
+    ///
+    /// The contents are borrowed from the source until an escape sequence or
+    /// stripped indentation makes them differ from it.
+    fn read_str_segment(
+        &mut self,
+        delimiter: StringDelimiter<'a>,
+        is_first_segment: bool,
+    ) -> Result<TokenKind<'a>, LexerError> {
+        let contents_start = self.source.index();
+        let mut str: Option<String> = delimiter.indentation.map(|_| String::new());
+        let mut is_at_line_start = is_first_segment && delimiter.indentation.is_some();
+        loop {
+            if is_at_line_start {
+                if self.read_multiline_closing_delimiter(delimiter.hashes) {
+                    let mut str = str.unwrap_or_default();
+                    if str.ends_with('\n') {
+                        str.pop();
+                    }
+                    return Ok(self.string_segment(str.into(), is_first_segment));
+                }
+                self.skip_indentation(&delimiter)?;
+                is_at_line_start = false;
+            }
+
+            if delimiter.indentation.is_none() && self.is_at_closing_delimiter(delimiter.hashes) {
+                let contents = self.str_contents(str, contents_start);
+                self.source.advance_by(delimiter.hashes + 1);
+                return Ok(self.string_segment(contents, is_first_segment));
+            }
+
+            let start = self.source.location();
+            match self.source.peek() {
+                Some('\\') if !delimiter.is_raw() && self.source.peek_next() == Some('(') => {
+                    let contents = self.str_contents(str, contents_start);
+                    self.string_state = Some(StringState::AtInterpolation(delimiter));
+                    return Ok(TokenKind::StringPart(self.interner.intern(contents)));
+                }
+                Some('\\')
+                    if !delimiter.is_raw()
+                        && self
+                            .source
+                            .peek_next()
+                            .is_some_and(|ch| ch != '\n' || delimiter.indentation.is_some()) =>
+                {
+                    let str = str
+                        .get_or_insert_with(|| self.source.text_from(contents_start).to_string());
+                    self.source.next();
+                    str.push(self.read_escape(start)?);
+                }
+                Some(ch) if ch != '\n' || delimiter.indentation.is_some() => {
+                    self.source.next();
+                    if let Some(str) = &mut str {
+                        str.push(ch);
+                    }
+                    is_at_line_start = ch == '\n';
+                }
+                _ => {
+                    let error = delimiter.unterminated_error();
+                    return Err(LexerError::new(
+                        error,
+                        delimiter.start,
+                        self.source.location(),
+                    ));
+                }
+            }
+        }
+    }
+
+    fn string_segment(&mut self, str: Cow<'a, str>, is_first_segment: bool) -> TokenKind<'a> {
+        let str = self.interner.intern(str);
+        if is_first_segment {
+            Literal::String(str).into()
+        } else {
+            TokenKind::StringPart(str)
+        }
+    }
+
+    /// The contents of a string segment starting at `contents_start`, which
+    /// are only copied into `str` once they differ from the source.
+    fn str_contents(&self, str: Option<String>, contents_start: usize) -> Cow<'a, str> {
+        match str {
+            Some(str) => Cow::Owned(str),
+            None => Cow::Borrowed(self.source.text_from(contents_start)),
+        }
+    }
+
+    /// Whether a `"` followed by `hashes` number of `#` is next.
+    fn is_at_closing_delimiter(&self, hashes: usize) -> bool {
+        is_closing_delimiter(self.source.rest(), "\"", hashes)
+    }
+
+    /// Reads an indented `"""` followed by `hashes` number of `#`.
+    fn read_multiline_closing_delimiter(&mut self, hashes: usize) -> bool {
+        let indentation = self.source.count_while(is_indentation);
+        let rest = &self.source.rest()[indentation..];
+        if !is_closing_delimiter(rest, "\"\"\"", hashes) {
+            return false;
+        }
+        self.source.advance_by(indentation + 3 + hashes);
+        true
+    }
+
+    /// Looks ahead for the line that closes a multi-line string and returns
+    /// the indentation before its delimiter.
+    fn find_closing_indentation(&self, hashes: usize) -> Option<&'a str> {
+        self.source.rest().split('\n').find_map(|line| {
+            let contents = line.trim_start_matches(is_indentation);
+            let indentation = &line[..line.len() - contents.len()];
+            is_closing_delimiter(contents, "\"\"\"", hashes).then_some(indentation)
+        })
+    }
 
-        let is_unterminated = self.source.peek() != Some('"');
+    /// Skips the indentation at the start of a line in a multi-line string.
+    /// Lines holding nothing but whitespace may be indented less.
+    fn skip_indentation(&mut self, delimiter: &StringDelimiter<'a>) -> Result<(), LexerError> {
+        let Some(indentation) = &delimiter.indentation else {
+            return Ok(());
+        };
+
+        let start = self.source.location();
+        for expected in indentation.chars() {
+            if self.source.next_if(|ch| ch == expected).is_some() {
+                continue;
+            }
 
-        if is_unterminated {
-            return Err(TokenizeError::UnterminatedString);
+            self.source.advance_while(is_indentation);
+            if matches!(self.source.peek(), Some('\n') | None) {
+                return Ok(());
+            }
+            let error = TokenizeError::InsufficientIndentation;
+            return self.report(LexerError::new(error, start, self.source.location()));
         }
+        Ok(())
+    }
 
+    fn read_interpolation_start(&mut self, delimiter: StringDelimiter<'a>) -> TokenKind<'a> {
+        self.source.next();
         self.source.next();
-        Ok(Some(str))
+        self.interpolations.push(Interpolation {
+            delimiter,
+            paren_depth: 0,
+        });
+        TokenKind::InterpolationStart
     }
 
-    fn read_number(&mut self) -> Option<Literal> {
-        let num = self
-            .source
-            .take_while(|ch| ch.is_ascii_digit())?
-            .parse::<i32>()
-            .ok()?;
-        Some(Literal::Int(num))
+    /// Keeps track of parentheses inside of an interpolation, turning the `)`
+    /// that closes it into an `InterpolationEnd`.
+    fn track_interpolation(&mut self, token_kind: TokenKind<'a>) -> TokenKind<'a> {
+        let Some(interpolation) = self.interpolations.last_mut() else {
+            return token_kind;
+        };
+
+        match token_kind {
+            TokenKind::OpenParen => interpolation.paren_depth += 1,
+            TokenKind::CloseParen if interpolation.paren_depth > 0 => {
+                interpolation.paren_depth -= 1
+            }
+            TokenKind::CloseParen => {
+                let delimiter = interpolation.delimiter.clone();
+                self.interpolations.pop();
+                self.string_state = Some(StringState::AfterInterpolation(delimiter));
+                return TokenKind::InterpolationEnd;
+            }
+            _ => {}
+        }
+        token_kind
+    }
+
+    /// Reads the remainder of an escape sequence whose `\` began at `start`.
+    fn read_escape(&mut self, start: SourceLocation) -> Result<char, LexerError> {
+        let ch = match self.source.next() {
+            Some('n') => '\n',
+            Some('t') => '\t',
+            Some('r') => '\r',
+            Some('0') => '\0',
+            Some('\\') => '\\',
+            Some('"') => '"',
+            Some('\'') => '\'',
+            Some('u') => return self.read_unicode_escape(start),
+            Some(ch) => {
+                let error = TokenizeError::UnknownEscape(ch);
+                self.report(LexerError::new(error, start, self.source.location()))?;
+                char::REPLACEMENT_CHARACTER
+            }
+            None => return Err(self.error(TokenizeError::UnterminatedString)),
+        };
+        Ok(ch)
+    }
+
+    /// Reads the `{1F600}` part of a `\u{1F600}` escape.
+    fn read_unicode_escape(&mut self, start: SourceLocation) -> Result<char, LexerError> {
+        let ch = self.source.next_if(|ch| ch == '{').and_then(|_| {
+            let digits = self.source.take_while(|ch| ch.is_ascii_hexdigit())?;
+            self.source.next_if(|ch| ch == '}')?;
+            if digits.len() > 8 {
+                return None;
+            }
+            char::from_u32(u32::from_str_radix(digits, 16).ok()?)
+        });
+
+        if let Some(ch) = ch {
+            return Ok(ch);
+        }
+
+        let error = TokenizeError::InvalidUnicodeEscape;
+        self.report(LexerError::new(error, start, self.source.location()))?;
+        Ok(char::REPLACEMENT_CHARACTER)
+    }
+
+    /// Reads an integer or floating point literal.
+    ///
+    /// Integers may be written in hex (`0xFF`), octal (`0o755`) or binary
+    /// (`0b1010`), and any literal may use `_` as a digit separator and end in
+    /// a type suffix such as `10u32` or `2.5f32`.
+    ///
+    /// A `.` only starts a fraction when it is directly followed by a digit, so
+    /// `5.description` is lexed as member access on an integer and `5.` as an
+    /// integer followed by a period. A leading `.` (as in `.5`) is never part
+    /// of a number.
+    fn read_number(&mut self) -> Result<Option<Literal<'a>>, LexerError> {
+        if !self.source.peek().is_some_and(|ch| ch.is_ascii_digit()) {
+            return Ok(None);
+        }
+
+        if let Some((prefix, radix)) = self.read_radix_prefix() {
+            return self.read_radix_integer(prefix, radix).map(Some);
+        }
+
+        let start = self.source.index();
+        self.read_decimal_digits();
+        let mut is_double = false;
+
+        let is_start_of_fraction = self.source.peek() == Some('.')
+            && self
+                .source
+                .peek_next()
+                .is_some_and(|ch| ch.is_ascii_digit());
+        if is_start_of_fraction {
+            self.source.next();
+            self.read_decimal_digits();
+            is_double = true;
+        }
+
+        if self.source.next_if(|ch| ch == 'e' || ch == 'E').is_some() {
+            self.source.next_if(|ch| ch == '+' || ch == '-');
+            if self.read_decimal_digits().is_empty() {
+                self.read_digits_and_letters();
+                let num = self.source.text_from(start).to_string();
+                return Err(self.error(TokenizeError::MalformedFloat(num)));
+            }
+            is_double = true;
+        }
+
+        let num = self.source.text_from(start);
+        let suffix = self.read_number_suffix(|suffix| !is_double || suffix.is_float())?;
+        let is_double = is_double || suffix.as_ref().is_some_and(NumberSuffix::is_float);
+        let digits = match num.contains('_') {
+            true => Cow::Owned(num.replace('_', "")),
+            false => Cow::Borrowed(num),
+        };
+
+        if !is_double {
+            let int = digits
+                .parse::<u128>()
+                .map_err(|_| self.error(TokenizeError::IntegerLiteralOverflow(num.to_string())))?;
+            return Ok(Some(Literal::Int(int, suffix)));
+        }
+
+        // Out of range values parse as infinity instead of failing.
+        match digits.parse::<f64>() {
+            Ok(double) if double.is_finite() => Ok(Some(Literal::Double(double, suffix))),
+            _ => Err(self.error(TokenizeError::MalformedFloat(num.to_string()))),
+        }
+    }
+
+    fn read_radix_prefix(&mut self) -> Option<(char, u32)> {
+        if self.source.peek() != Some('0') {
+            return None;
+        }
+        let prefix = self.source.peek_next()?;
+        let radix = match prefix {
+            'x' => 16,
+            'o' => 8,
+            'b' => 2,
+            _ => return None,
+        };
+        self.source.next();
+        self.source.next();
+        Some((prefix, radix))
+    }
+
+    fn read_radix_integer(&mut self, prefix: char, radix: u32) -> Result<Literal<'a>, LexerError> {
+        let mut digits = String::new();
+
+        while let Some(ch) = self.source.peek() {
+            let start = self.source.location();
+            if ch != '_' && !ch.is_ascii_digit() && !ch.is_digit(radix) {
+                break;
+            }
+            self.source.next();
+
+            if ch == '_' {
+                continue;
+            }
+            if !ch.is_digit(radix) {
+                let error = TokenizeError::InvalidDigit { digit: ch, radix };
+                self.report(LexerError::new(error, start, self.source.location()))?;
+                continue;
+            }
+            digits.push(ch);
+        }
+
+        if digits.is_empty() {
+            return Err(self.error(TokenizeError::MissingDigits));
+        }
+
+        let suffix = self.read_number_suffix(|suffix| !suffix.is_float())?;
+
+        match u128::from_str_radix(&digits, radix) {
+            Ok(int) => Ok(Literal::Int(int, suffix)),
+            Err(_) => {
+                let num = format!("0{prefix}{digits}");
+                Err(self.error(TokenizeError::IntegerLiteralOverflow(num)))
+            }
+        }
+    }
+
+    /// Reads a type suffix such as `u32` or `f64`, failing when the suffix is
+    /// unknown or not `accepted` for the literal it is attached to.
+    fn read_number_suffix(
+        &mut self,
+        accepted: impl Fn(&NumberSuffix) -> bool,
+    ) -> Result<Option<NumberSuffix>, LexerError> {
+        let start = self.source.location();
+        let Some(suffix) = self.source.take_while_from(
+            |ch| ch.is_ascii_alphabetic(),
+            |ch| ch.is_ascii_alphanumeric(),
+        ) else {
+            return Ok(None);
+        };
+
+        match NumberSuffix::from_str(suffix) {
+            Some(suffix) if accepted(&suffix) => Ok(Some(suffix)),
+            _ => {
+                let error = TokenizeError::InvalidNumberSuffix(suffix.to_string());
+                self.report(LexerError::new(error, start, self.source.location()))?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Reads digits and `_` separators, provided the first character is a digit.
+    fn read_decimal_digits(&mut self) -> &'a str {
+        self.source
+            .take_while_from(
+                |ch| ch.is_ascii_digit(),
+                |ch| ch.is_ascii_digit() || ch == '_',
+            )
+            .unwrap_or_default()
+    }
+
+    fn read_digits_and_letters(&mut self) -> &'a str {
+        self.source
+            .take_while(|ch| ch.is_ascii_alphanumeric())
+            .unwrap_or_default()
     }
 }
 
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Token<'a>, LexerError>;
+
+    /// Lexes the next token. Unless the lexer is recovering, lexing ends after
+    /// the first error.
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.is_failed {
+            return None;
+        }
+        match self.next_token() {
+            Ok(token) => token.map(Ok),
+            Err(error) if self.is_recovering => Some(Ok(self.recover(error))),
+            Err(error) => {
+                self.is_failed = true;
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+/// Whether `text` starts with `quotes` followed by `hashes` number of `#`.
+fn is_closing_delimiter(text: &str, quotes: &str, hashes: usize) -> bool {
+    text.strip_prefix(quotes).is_some_and(|rest| {
+        rest.as_bytes()
+            .get(..hashes)
+            .is_some_and(|rest| rest.iter().all(|byte| *byte == b'#'))
+    })
+}
+
+fn is_indentation(c: char) -> bool {
+    c == ' ' || c == '\t'
+}
+
+/// Identifiers follow UAX #31: they start with an XID_Start char or `_`,
+/// followed by XID_Continue chars.
 fn is_start_of_identifier(c: char) -> bool {
-    c.is_ascii_alphabetic() || c == '_'
+    c.is_xid_start() || c == '_'
 }
 
 fn is_identifier(c: char) -> bool {
-    is_start_of_identifier(c) || c.is_ascii_digit()
+    c.is_xid_continue()
+}
+
+/// Normalizes an identifier to NFC, so that identifiers which only differ in
+/// how their accents are encoded compare equal.
+fn normalize_identifier(identifier: &str) -> Cow<'_, str> {
+    if is_nfc(identifier) {
+        Cow::Borrowed(identifier)
+    } else {
+        Cow::Owned(identifier.nfc().collect())
+    }
 }
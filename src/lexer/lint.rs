@@ -0,0 +1,70 @@
+use super::token::{SourceLocation, Token, TokenKind};
+use std::{collections::HashMap, ops::Range};
+use unicode_security::{skeleton, MixedScript};
+
+/// A suspicious identifier, reported by `lint_identifiers` for security review.
+#[derive(Debug, PartialEq)]
+pub struct IdentifierLint {
+    lint: IdentifierLintKind,
+    location: Range<SourceLocation>,
+}
+
+impl IdentifierLint {
+    pub fn new(lint: IdentifierLintKind, start: SourceLocation, end: SourceLocation) -> Self {
+        Self {
+            lint,
+            location: start..end,
+        }
+    }
+
+    pub fn lint(&self) -> &IdentifierLintKind {
+        &self.lint
+    }
+
+    pub fn location(&self) -> &Range<SourceLocation> {
+        &self.location
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum IdentifierLintKind {
+    /// The identifier mixes scripts, like `pаypal` with a Cyrillic `а`.
+    MixedScript(String),
+    /// The identifier looks like another one used earlier, like `ѕсоре` in
+    /// Cyrillic and `scope` in Latin.
+    Confusable { identifier: String, other: String },
+}
+
+/// Flags identifiers that mix scripts or can be confused with another
+/// identifier in `tokens`. Like rustc, identifiers that are both ASCII are
+/// never reported as confusable.
+pub fn lint_identifiers(tokens: &[Token]) -> Vec<IdentifierLint> {
+    let mut lints: Vec<IdentifierLint> = Vec::new();
+    let mut skeletons: HashMap<String, &str> = HashMap::new();
+
+    for token in tokens {
+        let TokenKind::Identifier(identifier) = token.kind() else {
+            continue;
+        };
+        let identifier: &str = identifier;
+        let location = token.location().clone();
+
+        if !identifier.is_single_script() {
+            let lint = IdentifierLintKind::MixedScript(identifier.to_string());
+            lints.push(IdentifierLint::new(lint, location.start, location.end));
+            continue;
+        }
+
+        let other = *skeletons
+            .entry(skeleton(identifier).collect())
+            .or_insert(identifier);
+        if other != identifier && !(other.is_ascii() && identifier.is_ascii()) {
+            let lint = IdentifierLintKind::Confusable {
+                identifier: identifier.to_string(),
+                other: other.to_string(),
+            };
+            lints.push(IdentifierLint::new(lint, location.start, location.end));
+        }
+    }
+    lints
+}
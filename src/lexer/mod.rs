@@ -1,4 +1,7 @@
 pub mod error;
+pub mod interner;
 pub mod lib;
+pub mod lint;
 pub mod source;
+pub mod source_map;
 pub mod token;
@@ -1,17 +1,24 @@
-use super::token::SourceLocation;
-
-pub struct Source {
-    chars: Vec<char>,
+use super::{
+    error::{LexerError, TokenizeError},
+    source_map::FileId,
+    token::{SourceLocation, Trivia},
+};
+
+/// A cursor over the source text. `index` is a byte offset into `text` and
+/// always lies on a char boundary.
+pub struct Source<'a> {
+    file: FileId,
+    text: &'a str,
     index: usize,
     line_position: usize,
     column_position: usize,
 }
 
-impl Source {
-    pub fn new(source: &str) -> Self {
-        let chars: Vec<char> = source.chars().collect();
+impl<'a> Source<'a> {
+    pub fn new(file: FileId, text: &'a str) -> Self {
         Self {
-            chars,
+            file,
+            text,
             index: 0,
             line_position: 1,
             column_position: 1,
@@ -19,42 +26,131 @@ impl Source {
     }
 
     pub fn location(&self) -> SourceLocation {
-        SourceLocation::new(self.line_position, self.column_position)
+        SourceLocation::new(self.line_position, self.column_position, self.index).in_file(self.file)
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The source text from `start` up to the current index.
+    pub fn text_from(&self, start: usize) -> &'a str {
+        &self.text[start..self.index]
+    }
+
+    /// The source text that has not been read yet.
+    pub fn rest(&self) -> &'a str {
+        &self.text[self.index..]
     }
 
     pub fn is_eof(&self) -> bool {
-        self.peek().is_none()
+        self.index == self.text.len()
     }
 
-    pub fn advance_to_next_token(&mut self) {
-        while self.is_at_start_of_comment() || self.is_next_char_whitespace() {
+    /// Skips whitespace and comments, stopping in front of doc comments since
+    /// those are lexed as tokens.
+    pub fn advance_to_next_token(&mut self) -> Result<(), LexerError> {
+        while !self.is_at_start_of_doc_comment() {
             if self.is_at_start_of_comment() {
                 self.advance_to_next_line();
+            } else if self.is_at_start_of_block_comment() {
+                self.read_block_comment()?;
+            } else if self.is_next_char_whitespace() {
+                self.advance_while(|ch| ch.is_ascii_whitespace());
+            } else {
+                break;
             }
-            self.advance_while(|ch| ch.is_ascii_whitespace());
         }
+        Ok(())
     }
 
-    pub fn take_while(&mut self, condition: impl Fn(char) -> bool) -> Option<String> {
-        let first_ch = self.next_if(&condition)?;
-        let mut chars: Vec<char> = vec![first_ch];
+    /// Reads whitespace and comments like `advance_to_next_token`, but keeps
+    /// them. Trailing trivia ends in front of the next newline.
+    pub fn read_trivia(&mut self, is_trailing: bool) -> Result<Vec<Trivia<'a>>, LexerError> {
+        let mut trivia: Vec<Trivia> = Vec::new();
 
-        while let Some(ch) = self.next_if(&condition) {
-            chars.push(ch);
+        while !self.is_at_start_of_doc_comment() {
+            let start = self.index;
+            if self.is_at_start_of_comment() {
+                self.advance_while(|ch| ch != '\n');
+                trivia.push(Trivia::LineComment(self.text_from(start)));
+            } else if self.is_at_start_of_block_comment() {
+                self.read_block_comment()?;
+                trivia.push(Trivia::BlockComment(self.text_from(start)));
+            } else if self.peek() == Some('\n') && !is_trailing {
+                self.next();
+                trivia.push(Trivia::Newline);
+            } else if self.is_next_char_whitespace() && self.peek() != Some('\n') {
+                self.advance_while(|ch| ch.is_ascii_whitespace() && ch != '\n');
+                trivia.push(Trivia::Whitespace(self.text_from(start)));
+            } else {
+                break;
+            }
         }
+        Ok(trivia)
+    }
 
-        let str = String::from_iter(chars);
-        Some(str)
+    /// Whether the upcoming chars are `///` or `/**`. Like in Rust, `////`,
+    /// `/***` and `/**/` are regular comments.
+    pub fn is_at_start_of_doc_comment(&self) -> bool {
+        let is_doc_line_comment = self.is_next("///") && !self.is_next("////");
+        let is_doc_block_comment =
+            self.is_next("/**") && !self.is_next("/***") && !self.is_next("/**/");
+        is_doc_line_comment || is_doc_block_comment
     }
 
-    pub fn advance_while(&mut self, condition: impl Fn(char) -> bool) {
+    /// Reads a possibly nested `/* ... */` comment, returning the text between
+    /// its outermost delimiters.
+    pub fn read_block_comment(&mut self) -> Result<&'a str, LexerError> {
+        let start = self.location();
+        self.advance_by(2);
+        let text_start = self.index;
+
+        let mut depth = 1;
         loop {
-            if self.next_if(&condition).is_none() {
-                break;
+            if self.is_next("/*") {
+                depth += 1;
+                self.advance_by(2);
+                continue;
+            }
+            if self.is_next("*/") {
+                depth -= 1;
+                if depth == 0 {
+                    let text = self.text_from(text_start);
+                    self.advance_by(2);
+                    return Ok(text);
+                }
+                self.advance_by(2);
+                continue;
+            }
+
+            if self.next().is_none() {
+                let error = TokenizeError::UnterminatedBlockComment;
+                return Err(LexerError::new(error, start, self.location()));
             }
         }
     }
 
+    pub fn take_while(&mut self, condition: impl Fn(char) -> bool) -> Option<&'a str> {
+        self.take_while_from(&condition, &condition)
+    }
+
+    /// Like `take_while`, but the first char must satisfy `first` instead.
+    pub fn take_while_from(
+        &mut self,
+        first: impl Fn(char) -> bool,
+        condition: impl Fn(char) -> bool,
+    ) -> Option<&'a str> {
+        let start = self.index;
+        self.next_if(first)?;
+        self.advance_while(condition);
+        Some(self.text_from(start))
+    }
+
+    pub fn advance_while(&mut self, condition: impl Fn(char) -> bool) {
+        while self.next_if(&condition).is_some() {}
+    }
+
     pub fn advance_past_next(&mut self, ch: char) {
         self.advance_while(|c| c != ch);
         if self.peek().is_some() {
@@ -63,13 +159,31 @@ impl Source {
     }
 
     pub fn peek(&self) -> Option<char> {
-        let ch = *self.chars.get(self.index)?;
-        Some(ch)
+        self.rest().chars().next()
     }
 
     pub fn peek_next(&self) -> Option<char> {
-        let ch = *self.chars.get(self.index + 1)?;
-        Some(ch)
+        self.peek_nth(1)
+    }
+
+    pub fn peek_nth(&self, n: usize) -> Option<char> {
+        self.rest().chars().nth(n)
+    }
+
+    /// Whether the upcoming chars are exactly `s`.
+    pub fn is_next(&self, s: &str) -> bool {
+        self.rest().starts_with(s)
+    }
+
+    /// Counts how many of the upcoming chars satisfy `condition`.
+    pub fn count_while(&self, condition: impl Fn(char) -> bool) -> usize {
+        self.rest().chars().take_while(|ch| condition(*ch)).count()
+    }
+
+    pub fn advance_by(&mut self, n: usize) {
+        for _ in 0..n {
+            self.next();
+        }
     }
 
     pub fn next_if(&mut self, condition: impl Fn(char) -> bool) -> Option<char> {
@@ -77,7 +191,7 @@ impl Source {
     }
 
     pub fn next_map<T>(&mut self, map: impl Fn(char) -> Option<T>) -> Option<T> {
-        let next = *self.chars.get(self.index)?;
+        let next = self.peek()?;
         let result = map(next);
         if result.is_some() {
             self.increment_cursor(next);
@@ -85,8 +199,10 @@ impl Source {
         result
     }
 
+    // Not an `Iterator`, since `Source` is peeked and advanced by its own methods.
+    #[allow(clippy::should_implement_trait)]
     pub fn next(&mut self) -> Option<char> {
-        let next = *self.chars.get(self.index)?;
+        let next = self.peek()?;
 
         self.increment_cursor(next);
 
@@ -101,11 +217,15 @@ impl Source {
             self.column_position += 1;
         }
 
-        self.index += 1;
+        self.index += ch.len_utf8();
     }
 
     fn is_at_start_of_comment(&self) -> bool {
-        self.peek() == Some('/') && self.peek_next() == Some('/')
+        self.is_next("//")
+    }
+
+    fn is_at_start_of_block_comment(&self) -> bool {
+        self.is_next("/*")
     }
 
     fn is_next_char_whitespace(&self) -> bool {
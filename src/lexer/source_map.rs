@@ -0,0 +1,119 @@
+use super::token::SourceLocation;
+use std::ops::Range;
+
+/// Identifies a file in a `SourceMap`. Sources lexed without a `SourceMap` are
+/// treated as its first file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct FileId(usize);
+
+/// The unit a column is counted in. Rust strings are indexed in UTF-8 bytes,
+/// while editors speaking the Language Server Protocol count UTF-16 units.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ColumnUnit {
+    Utf8,
+    Utf16,
+    Char,
+}
+
+/// All files of a program, so that locations in diagnostics can be resolved to
+/// the file they point into.
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_file(&mut self, name: &str, text: &str) -> FileId {
+        let file = FileId(self.files.len());
+        self.files.push(SourceFile::new(name, text));
+        file
+    }
+
+    pub fn file(&self, file: FileId) -> Option<&SourceFile> {
+        self.files.get(file.0)
+    }
+
+    /// The text a range of locations spans in the file it points into.
+    pub fn slice(&self, location: &Range<SourceLocation>) -> Option<&str> {
+        self.file(location.start.file())?.slice(location)
+    }
+}
+
+pub struct SourceFile {
+    name: String,
+    text: String,
+    /// The byte offset each line starts at.
+    line_starts: Vec<usize>,
+}
+
+impl SourceFile {
+    pub fn new(name: &str, text: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(text.match_indices('\n').map(|(offset, _)| offset + 1))
+            .collect();
+        Self {
+            name: name.to_string(),
+            text: text.to_string(),
+            line_starts,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// The 1-based line and column of a byte offset, with the column counted in
+    /// `unit`. Returns `None` if the offset is out of bounds or not at a char
+    /// boundary.
+    pub fn line_column(&self, offset: usize, unit: ColumnUnit) -> Option<(usize, usize)> {
+        let line = self.line_starts.partition_point(|start| *start <= offset);
+        let line_start = self.line_starts[line - 1];
+        let prefix = self.text.get(line_start..offset)?;
+
+        let column = match unit {
+            ColumnUnit::Utf8 => prefix.len(),
+            ColumnUnit::Utf16 => prefix.encode_utf16().count(),
+            ColumnUnit::Char => prefix.chars().count(),
+        };
+        Some((line, column + 1))
+    }
+
+    /// The byte offset of a 1-based line and column counted in `unit`.
+    pub fn offset(&self, line: usize, column: usize, unit: ColumnUnit) -> Option<usize> {
+        let line_start = *self.line_starts.get(line.checked_sub(1)?)?;
+        let line_end = self
+            .line_starts
+            .get(line)
+            .map_or(self.text.len(), |next_line_start| next_line_start - 1);
+
+        let mut units = 1;
+        for (offset, ch) in self.text[line_start..line_end].char_indices() {
+            if units >= column {
+                return (units == column).then_some(line_start + offset);
+            }
+            units += match unit {
+                ColumnUnit::Utf8 => ch.len_utf8(),
+                ColumnUnit::Utf16 => ch.len_utf16(),
+                ColumnUnit::Char => 1,
+            };
+        }
+        (units == column).then_some(line_end)
+    }
+
+    pub fn slice(&self, location: &Range<SourceLocation>) -> Option<&str> {
+        self.text
+            .get(location.start.offset()..location.end.offset())
+    }
+}
@@ -1,31 +1,135 @@
+use super::{interner::Text, source_map::FileId};
 use std::ops::Range;
 
 #[derive(Debug, Clone, PartialEq)]
-pub struct Token {
-    kind: TokenKind,
+pub struct Token<'a> {
+    kind: TokenKind<'a>,
     location: Range<SourceLocation>,
+    /// The token's source text and the trivia around it, only kept by
+    /// `Lexer::tokenize_lossless`.
+    trivia: Option<Box<TokenTrivia<'a>>>,
 }
 
-impl Token {
-    pub fn new(kind: TokenKind, start: SourceLocation, end: SourceLocation) -> Self {
+#[derive(Debug, Clone, PartialEq)]
+struct TokenTrivia<'a> {
+    leading: Vec<Trivia<'a>>,
+    text: &'a str,
+    trailing: Vec<Trivia<'a>>,
+}
+
+impl<'a> Token<'a> {
+    pub fn new(kind: TokenKind<'a>, start: SourceLocation, end: SourceLocation) -> Self {
         let location = start..end;
-        Self { kind, location }
+        Self {
+            kind,
+            location,
+            trivia: None,
+        }
+    }
+
+    pub fn with_trivia(
+        self,
+        leading: Vec<Trivia<'a>>,
+        text: &'a str,
+        trailing: Vec<Trivia<'a>>,
+    ) -> Self {
+        let trivia = TokenTrivia {
+            leading,
+            text,
+            trailing,
+        };
+        Self {
+            trivia: Some(Box::new(trivia)),
+            ..self
+        }
     }
 
-    pub fn kind(&self) -> &TokenKind {
+    pub fn kind(&self) -> &TokenKind<'a> {
         &self.kind
     }
+
+    pub fn into_kind(self) -> TokenKind<'a> {
+        self.kind
+    }
+
+    pub fn location(&self) -> &Range<SourceLocation> {
+        &self.location
+    }
+
+    pub fn leading_trivia(&self) -> &[Trivia<'a>] {
+        self.trivia.as_ref().map_or(&[], |trivia| &trivia.leading)
+    }
+
+    pub fn trailing_trivia(&self) -> &[Trivia<'a>] {
+        self.trivia.as_ref().map_or(&[], |trivia| &trivia.trailing)
+    }
+
+    /// The exact source text of the token including its trivia, if the token
+    /// was lexed losslessly.
+    pub fn source_text(&self) -> Option<String> {
+        let trivia = self.trivia.as_ref()?;
+        let mut text = String::new();
+        trivia.leading.iter().for_each(|t| text.push_str(t.text()));
+        text.push_str(trivia.text);
+        trivia.trailing.iter().for_each(|t| text.push_str(t.text()));
+        Some(text)
+    }
+}
+
+/// Source text between tokens that does not affect the program.
+///
+/// Trivia up to the end of a token's line belongs to that token as trailing
+/// trivia, everything else to the next token as leading trivia.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Trivia<'a> {
+    Whitespace(&'a str),
+    Newline,
+    LineComment(&'a str),
+    BlockComment(&'a str),
+}
+
+impl<'a> Trivia<'a> {
+    pub fn text(&self) -> &'a str {
+        match self {
+            Trivia::Whitespace(text) => text,
+            Trivia::Newline => "\n",
+            Trivia::LineComment(text) => text,
+            Trivia::BlockComment(text) => text,
+        }
+    }
 }
 
+/// A position in a source file. `line` and `column` are 1-based and count
+/// chars, `offset` is the 0-based byte offset into the file's text. Use a
+/// `SourceFile` to get columns in other units.
 #[derive(Debug, Clone, PartialEq)]
 pub struct SourceLocation {
+    file: FileId,
+    offset: usize,
     line: usize,
     column: usize,
 }
 
 impl SourceLocation {
-    pub fn new(line: usize, column: usize) -> Self {
-        Self { line, column }
+    pub fn new(line: usize, column: usize, offset: usize) -> Self {
+        Self {
+            file: FileId::default(),
+            offset,
+            line,
+            column,
+        }
+    }
+
+    pub fn in_file(self, file: FileId) -> Self {
+        Self { file, ..self }
+    }
+
+    pub fn file(&self) -> FileId {
+        self.file
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
     }
 
     pub fn line(&self) -> usize {
@@ -37,19 +141,36 @@ impl SourceLocation {
     }
 }
 
+/// Text is borrowed from the source, except where escapes, stripped
+/// indentation or Unicode normalization make it differ from the source.
 #[derive(Debug, Clone, PartialEq)]
-pub enum TokenKind {
+pub enum TokenKind<'a> {
     Eq,
     Plus,
     Minus,
     Asterisk,
     Slash,
+    Percent,
+    PlusEq,
+    MinusEq,
+    AsteriskEq,
+    SlashEq,
+    PercentEq,
     NotEq,
     GreaterThan,
     GreaterThanEq,
     EqEq,
     LessThan,
     LessThanEq,
+    AndAnd,
+    OrOr,
+    Ampersand,
+    Pipe,
+    Caret,
+    Tilde,
+    ShiftLeft,
+    ShiftRight,
+    Arrow,
     OpenBrace,
     CloseBrace,
     OpenParen,
@@ -62,48 +183,108 @@ pub enum TokenKind {
     Period,
     QuestionMark,
     Negate,
-    Literal(Literal),
-    Identifier(String),
+    /// `??`
+    NilCoalescing,
+    /// `?.`, only lexed when the `?` directly follows the optional value.
+    OptionalChain,
+    /// `..<`
+    HalfOpenRange,
+    /// `...`
+    Ellipsis,
+    /// `..=`, a closed range like `...`.
+    InclusiveRange,
+    Literal(Literal<'a>),
+    /// A literal segment of an interpolated string.
+    StringPart(Text<'a>),
+    /// The `\(` that opens an interpolation inside of a string.
+    InterpolationStart,
+    /// The `)` that closes an interpolation inside of a string.
+    InterpolationEnd,
+    Identifier(Text<'a>),
+    /// An anonymous closure argument such as `$0`.
+    ClosureArg(u32),
     Keyword(Keyword),
+    /// The text of a `///` or `/** */` comment documenting the declaration
+    /// that follows it.
+    DocComment(&'a str),
+    /// The name of an attribute such as `@inline`, which applies to the
+    /// declaration that follows it.
+    Attribute(Text<'a>),
+    /// Invalid input skipped by `Lexer::tokenize_recovering`.
+    Error,
+    /// The end of the source, only emitted by `Lexer::tokenize_lossless` to
+    /// carry the trivia at the end of the file.
+    Eof,
 }
 
-impl TokenKind {
-    pub fn from_char(ch: char) -> Option<TokenKind> {
-        match ch {
-            '=' => Some(TokenKind::Eq),
-            '+' => Some(TokenKind::Plus),
-            '-' => Some(TokenKind::Minus),
-            '*' => Some(TokenKind::Asterisk),
-            '/' => Some(TokenKind::Slash),
-            '>' => Some(TokenKind::GreaterThan),
-            '<' => Some(TokenKind::LessThan),
-            '{' => Some(TokenKind::OpenBrace),
-            '}' => Some(TokenKind::CloseBrace),
-            '(' => Some(TokenKind::OpenParen),
-            ')' => Some(TokenKind::CloseParen),
-            '[' => Some(TokenKind::OpenBracket),
-            ']' => Some(TokenKind::CloseBracket),
-            ',' => Some(TokenKind::Comma),
-            ';' => Some(TokenKind::Semicolon),
-            ':' => Some(TokenKind::Colon),
-            '.' => Some(TokenKind::Period),
-            '?' => Some(TokenKind::QuestionMark),
-            '!' => Some(TokenKind::Negate),
+/// The length of the longest string `TokenKind::from_operator` accepts.
+pub const MAX_OPERATOR_LEN: usize = 3;
+
+impl TokenKind<'_> {
+    pub fn from_operator(s: &str) -> Option<TokenKind<'static>> {
+        match s {
+            "=" => Some(TokenKind::Eq),
+            "+" => Some(TokenKind::Plus),
+            "-" => Some(TokenKind::Minus),
+            "*" => Some(TokenKind::Asterisk),
+            "/" => Some(TokenKind::Slash),
+            "%" => Some(TokenKind::Percent),
+            "+=" => Some(TokenKind::PlusEq),
+            "-=" => Some(TokenKind::MinusEq),
+            "*=" => Some(TokenKind::AsteriskEq),
+            "/=" => Some(TokenKind::SlashEq),
+            "%=" => Some(TokenKind::PercentEq),
+            "!=" => Some(TokenKind::NotEq),
+            ">" => Some(TokenKind::GreaterThan),
+            ">=" => Some(TokenKind::GreaterThanEq),
+            "==" => Some(TokenKind::EqEq),
+            "<" => Some(TokenKind::LessThan),
+            "<=" => Some(TokenKind::LessThanEq),
+            "&&" => Some(TokenKind::AndAnd),
+            "||" => Some(TokenKind::OrOr),
+            "&" => Some(TokenKind::Ampersand),
+            "|" => Some(TokenKind::Pipe),
+            "^" => Some(TokenKind::Caret),
+            "~" => Some(TokenKind::Tilde),
+            "<<" => Some(TokenKind::ShiftLeft),
+            ">>" => Some(TokenKind::ShiftRight),
+            "->" => Some(TokenKind::Arrow),
+            "{" => Some(TokenKind::OpenBrace),
+            "}" => Some(TokenKind::CloseBrace),
+            "(" => Some(TokenKind::OpenParen),
+            ")" => Some(TokenKind::CloseParen),
+            "[" => Some(TokenKind::OpenBracket),
+            "]" => Some(TokenKind::CloseBracket),
+            "," => Some(TokenKind::Comma),
+            ";" => Some(TokenKind::Semicolon),
+            ":" => Some(TokenKind::Colon),
+            "." => Some(TokenKind::Period),
+            "?" => Some(TokenKind::QuestionMark),
+            "!" => Some(TokenKind::Negate),
+            "??" => Some(TokenKind::NilCoalescing),
+            "?." => Some(TokenKind::OptionalChain),
+            "..<" => Some(TokenKind::HalfOpenRange),
+            "..." => Some(TokenKind::Ellipsis),
+            "..=" => Some(TokenKind::InclusiveRange),
             _ => None,
         }
     }
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub enum Literal {
-    Int(i32),
-    Double(f32),
+pub enum Literal<'a> {
+    /// Integer literals are stored unsigned and as wide as possible. A leading
+    /// `-` is a separate token, and whether the value fits the annotated type
+    /// (`u32`, `i32`, ...) is decided once types are known.
+    Int(u128, Option<NumberSuffix>),
+    Double(f64, Option<NumberSuffix>),
     Bool(bool),
-    String(String),
+    Char(char),
+    String(Text<'a>),
 }
 
-impl Literal {
-    pub fn as_bool(s: &str) -> Option<Literal> {
+impl Literal<'_> {
+    pub fn as_bool(s: &str) -> Option<Literal<'static>> {
         let result = match s {
             "true" => true.into(),
             "false" => false.into(),
@@ -113,8 +294,51 @@ impl Literal {
     }
 }
 
-impl From<Literal> for TokenKind {
-    fn from(literal: Literal) -> TokenKind {
+/// An explicit type written after a numeric literal, as in `10u32` or `2.5f32`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum NumberSuffix {
+    I8,
+    I16,
+    I32,
+    I64,
+    I128,
+    U8,
+    U16,
+    U32,
+    U64,
+    U128,
+    F32,
+    F64,
+}
+
+impl NumberSuffix {
+    // Mirrors `FromStr::from_str` without an error type to implement it with.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<NumberSuffix> {
+        match s {
+            "i8" => NumberSuffix::I8.into(),
+            "i16" => NumberSuffix::I16.into(),
+            "i32" => NumberSuffix::I32.into(),
+            "i64" => NumberSuffix::I64.into(),
+            "i128" => NumberSuffix::I128.into(),
+            "u8" => NumberSuffix::U8.into(),
+            "u16" => NumberSuffix::U16.into(),
+            "u32" => NumberSuffix::U32.into(),
+            "u64" => NumberSuffix::U64.into(),
+            "u128" => NumberSuffix::U128.into(),
+            "f32" => NumberSuffix::F32.into(),
+            "f64" => NumberSuffix::F64.into(),
+            _ => None,
+        }
+    }
+
+    pub fn is_float(&self) -> bool {
+        matches!(self, NumberSuffix::F32 | NumberSuffix::F64)
+    }
+}
+
+impl<'a> From<Literal<'a>> for TokenKind<'a> {
+    fn from(literal: Literal<'a>) -> TokenKind<'a> {
         TokenKind::Literal(literal)
     }
 }
@@ -122,25 +346,104 @@ impl From<Literal> for TokenKind {
 #[derive(Debug, Clone, PartialEq)]
 pub enum Keyword {
     Let,
+    Var,
     Fn,
     If,
     Else,
+    While,
+    For,
+    In,
+    Match,
+    Return,
+    Break,
+    Continue,
+    Struct,
+    Enum,
+    Trait,
+    Extend,
+    Import,
+    /// `self`
+    SelfValue,
+    Nil,
+    Throw,
+    Try,
+    Await,
+    Async,
+    Throws,
 }
 
 impl Keyword {
+    // Mirrors `FromStr::from_str` without an error type to implement it with.
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Keyword> {
         match s {
             "let" => Keyword::Let.into(),
+            "var" => Keyword::Var.into(),
             "fn" => Keyword::Fn.into(),
             "if" => Keyword::If.into(),
             "else" => Keyword::Else.into(),
+            "while" => Keyword::While.into(),
+            "for" => Keyword::For.into(),
+            "in" => Keyword::In.into(),
+            "match" => Keyword::Match.into(),
+            "return" => Keyword::Return.into(),
+            "break" => Keyword::Break.into(),
+            "continue" => Keyword::Continue.into(),
+            "struct" => Keyword::Struct.into(),
+            "enum" => Keyword::Enum.into(),
+            "trait" => Keyword::Trait.into(),
+            "extend" => Keyword::Extend.into(),
+            "import" => Keyword::Import.into(),
+            "self" => Keyword::SelfValue.into(),
+            "nil" => Keyword::Nil.into(),
+            "throw" => Keyword::Throw.into(),
+            "try" => Keyword::Try.into(),
+            "await" => Keyword::Await.into(),
+            "async" => Keyword::Async.into(),
+            "throws" => Keyword::Throws.into(),
             _ => None,
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Keyword::Let => "let",
+            Keyword::Var => "var",
+            Keyword::Fn => "fn",
+            Keyword::If => "if",
+            Keyword::Else => "else",
+            Keyword::While => "while",
+            Keyword::For => "for",
+            Keyword::In => "in",
+            Keyword::Match => "match",
+            Keyword::Return => "return",
+            Keyword::Break => "break",
+            Keyword::Continue => "continue",
+            Keyword::Struct => "struct",
+            Keyword::Enum => "enum",
+            Keyword::Trait => "trait",
+            Keyword::Extend => "extend",
+            Keyword::Import => "import",
+            Keyword::SelfValue => "self",
+            Keyword::Nil => "nil",
+            Keyword::Throw => "throw",
+            Keyword::Try => "try",
+            Keyword::Await => "await",
+            Keyword::Async => "async",
+            Keyword::Throws => "throws",
+        }
+    }
+
+    /// Contextual keywords only have a special meaning in one position (`async`
+    /// and `throws` after a function's parameters) and are still lexed as
+    /// keywords, but may be used as identifiers anywhere else.
+    pub fn is_contextual(&self) -> bool {
+        matches!(self, Keyword::Async | Keyword::Throws)
+    }
 }
 
-impl From<Keyword> for TokenKind {
-    fn from(keyword: Keyword) -> TokenKind {
+impl From<Keyword> for TokenKind<'_> {
+    fn from(keyword: Keyword) -> Self {
         TokenKind::Keyword(keyword)
     }
 }
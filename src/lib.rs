@@ -0,0 +1,7 @@
+pub mod driver;
+pub mod error;
+pub mod lexer;
+pub mod parser;
+
+#[cfg(test)]
+mod tests;
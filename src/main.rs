@@ -1,22 +1,41 @@
-mod driver;
-mod error;
-mod lexer;
-pub mod parser;
-
-#[cfg(test)]
-mod tests;
-
-use crate::{lexer::lib::Lexer, parser::Parser};
-use error::ShabaCompilerError;
+use shaba_lang::{
+    driver,
+    error::ShabaCompilerError,
+    lexer::{lib::Lexer, lint::lint_identifiers},
+    parser::Parser,
+};
 
 fn main() -> Result<(), ShabaCompilerError> {
     let source = driver::read_source()?;
-    let mut lexer = Lexer::new(source.as_str());
-    let tokens = lexer.tokenize()?;
 
-    dbg!(tokens.clone());
+    // The parser pulls tokens from its own lexer, so the source is only
+    // lexed up front when the tokens themselves were asked for.
+    let is_identifier_lint_enabled = driver::is_identifier_lint_enabled();
+    let is_token_dump_enabled = driver::is_token_dump_enabled();
+    if is_identifier_lint_enabled || is_token_dump_enabled {
+        let (tokens, _) = Lexer::new(source.as_str()).tokenize_recovering();
+        if is_identifier_lint_enabled {
+            for lint in lint_identifiers(&tokens) {
+                eprintln!("{:?}", lint);
+            }
+        }
+        if is_token_dump_enabled {
+            dbg!(tokens);
+        }
+    }
+
+    let (program, errors) = Parser::new(Lexer::new(source.as_str())).parse();
+
+    for error in &errors {
+        eprintln!("{:?}", error);
+    }
+    if let Some(error) = errors.into_iter().next() {
+        return Err(error.into());
+    }
 
-    let mut _parser = Parser::new(tokens);
+    if driver::is_ast_dump_enabled() {
+        dbg!(program);
+    }
 
     Ok(())
 }
@@ -0,0 +1,324 @@
+use crate::lexer::{
+    interner::Text,
+    token::{Literal, SourceLocation},
+};
+use std::ops::Range;
+
+/// The statements of a source file, in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Program<'a> {
+    pub statements: Vec<Statement<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Identifier<'a> {
+    pub name: Text<'a>,
+    pub location: Range<SourceLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Statement<'a> {
+    pub kind: StatementKind<'a>,
+    pub location: Range<SourceLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StatementKind<'a> {
+    Declaration(Box<Declaration<'a>>),
+    Expression(Expression<'a>),
+    Return(Option<Expression<'a>>),
+    /// The expression ending the body of a function or computed property,
+    /// whose value is returned.
+    ImplicitReturn(Expression<'a>),
+    Throw(Expression<'a>),
+    Break,
+    Continue,
+    If(If<'a>),
+    While {
+        condition: Expression<'a>,
+        body: Block<'a>,
+    },
+    For {
+        variable: Identifier<'a>,
+        iterable: Expression<'a>,
+        body: Block<'a>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct If<'a> {
+    pub condition: Expression<'a>,
+    pub then_branch: Block<'a>,
+    pub else_branch: Option<Else<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Else<'a> {
+    Block(Block<'a>),
+    /// `else if`
+    If(Box<If<'a>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block<'a> {
+    pub statements: Vec<Statement<'a>>,
+    pub location: Range<SourceLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Declaration<'a> {
+    pub kind: DeclarationKind<'a>,
+    /// The text of the doc comments in front of the declaration.
+    pub doc_comments: Vec<&'a str>,
+    pub attributes: Vec<Attribute<'a>>,
+    pub location: Range<SourceLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeclarationKind<'a> {
+    Variable(VariableDeclaration<'a>),
+    Function(FunctionDeclaration<'a>),
+    Struct(StructDeclaration<'a>),
+    Extend(ExtendDeclaration<'a>),
+}
+
+/// An attribute such as `@inline` or `@deprecated("use g")`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attribute<'a> {
+    pub name: Identifier<'a>,
+    pub arguments: Vec<Argument<'a>>,
+    pub location: Range<SourceLocation>,
+}
+
+/// A `let` or `var` binding, which also declares struct fields and computed
+/// properties.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableDeclaration<'a> {
+    pub is_mutable: bool,
+    pub name: Identifier<'a>,
+    pub type_annotation: Option<Type<'a>>,
+    pub initializer: Option<Initializer<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Initializer<'a> {
+    /// `let a = 1`
+    Value(Expression<'a>),
+    /// `let a: i32 { 1 }`, evaluated on every access.
+    Computed(Block<'a>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionDeclaration<'a> {
+    pub name: Identifier<'a>,
+    pub signature: FunctionSignature<'a>,
+    pub body: Block<'a>,
+}
+
+/// Everything between a function's name and its body, as in
+/// `(_ value: i32) async throws -> i32, SomeError`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FunctionSignature<'a> {
+    pub parameters: Vec<Parameter<'a>>,
+    pub is_async: bool,
+    pub is_throwing: bool,
+    pub return_type: Option<Type<'a>>,
+    /// The error types listed after the return type of a `throws` function.
+    pub error_types: Vec<Type<'a>>,
+    pub location: Range<SourceLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Parameter<'a> {
+    /// The label callers write in front of the argument, which is the name
+    /// unless another label is given. `None` for parameters labeled `_`.
+    pub label: Option<Identifier<'a>>,
+    pub name: Identifier<'a>,
+    pub type_annotation: Type<'a>,
+    /// Whether the parameter takes any number of arguments, as in
+    /// `_ values: i32...`.
+    pub is_variadic: bool,
+    pub default_value: Option<Expression<'a>>,
+    pub location: Range<SourceLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StructDeclaration<'a> {
+    pub name: Identifier<'a>,
+    pub members: Vec<Declaration<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ExtendDeclaration<'a> {
+    pub extended_type: Identifier<'a>,
+    pub members: Vec<Declaration<'a>>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Type<'a> {
+    pub kind: TypeKind<'a>,
+    pub location: Range<SourceLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypeKind<'a> {
+    Named(Identifier<'a>),
+    /// `Map<String, i32>`
+    Generic {
+        name: Identifier<'a>,
+        arguments: Vec<Type<'a>>,
+    },
+    /// `i32?`
+    Optional(Box<Type<'a>>),
+    /// `i32[]`
+    Array(Box<Type<'a>>),
+    /// `(i32, String)`, or `()` for no value.
+    Tuple(Vec<Type<'a>>),
+    /// `(i32) async throws -> Bool`
+    Function {
+        parameters: Vec<Type<'a>>,
+        is_async: bool,
+        is_throwing: bool,
+        return_type: Box<Type<'a>>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Expression<'a> {
+    pub kind: ExpressionKind<'a>,
+    pub location: Range<SourceLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExpressionKind<'a> {
+    Literal(Literal<'a>),
+    InterpolatedString(Vec<StringSegment<'a>>),
+    Identifier(Identifier<'a>),
+    /// An anonymous closure argument such as `$0`.
+    ClosureArg(u32),
+    SelfValue,
+    Nil,
+    Array(Vec<Expression<'a>>),
+    Unary {
+        operator: UnaryOperator,
+        operand: Box<Expression<'a>>,
+    },
+    Binary {
+        operator: BinaryOperator,
+        left: Box<Expression<'a>>,
+        right: Box<Expression<'a>>,
+    },
+    Assignment {
+        operator: AssignmentOperator,
+        target: Box<Expression<'a>>,
+        value: Box<Expression<'a>>,
+    },
+    Call {
+        callee: Box<Expression<'a>>,
+        arguments: Vec<Argument<'a>>,
+        /// A closure written after the parentheses, as in `arr.first { $0 }`.
+        trailing_closure: Option<Box<Closure<'a>>>,
+    },
+    MemberAccess {
+        object: Box<Expression<'a>>,
+        member: Identifier<'a>,
+    },
+    /// `array[index]`
+    Index {
+        object: Box<Expression<'a>>,
+        index: Box<Expression<'a>>,
+    },
+    /// The `a?` in `a?.b`, `a?[0]` or `a?()`, which makes the postfix
+    /// operators after it evaluate to `nil` when `a` is `nil`.
+    OptionalChain(Box<Expression<'a>>),
+    Closure(Box<Closure<'a>>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum StringSegment<'a> {
+    Text(Text<'a>),
+    Interpolation(Expression<'a>),
+}
+
+/// An argument of a call, as in `Person(name: "joe")`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Argument<'a> {
+    pub label: Option<Identifier<'a>>,
+    pub value: Expression<'a>,
+    pub location: Range<SourceLocation>,
+}
+
+/// A closure such as `{ $0 < 10 }` or `{ x, y in x + y }`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Closure<'a> {
+    /// `None` for closures without `in`, which use `$0`, `$1`, ... for their
+    /// arguments.
+    pub signature: Option<ClosureSignature<'a>>,
+    pub body: Vec<Statement<'a>>,
+    pub location: Range<SourceLocation>,
+}
+
+/// Everything up to the `in` of a closure, as in
+/// `(x: i32, y) async throws -> i32 in`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosureSignature<'a> {
+    pub parameters: Vec<ClosureParameter<'a>>,
+    pub is_async: bool,
+    pub is_throwing: bool,
+    pub return_type: Option<Type<'a>>,
+    pub location: Range<SourceLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosureParameter<'a> {
+    pub name: Identifier<'a>,
+    pub type_annotation: Option<Type<'a>>,
+    pub location: Range<SourceLocation>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UnaryOperator {
+    /// `!`
+    Not,
+    /// `-`
+    Negate,
+    /// `~`
+    BitwiseNot,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum BinaryOperator {
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+    Eq,
+    NotEq,
+    LessThan,
+    LessThanEq,
+    GreaterThan,
+    GreaterThanEq,
+    And,
+    Or,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    ShiftLeft,
+    ShiftRight,
+    NilCoalescing,
+    /// `..<`
+    HalfOpenRange,
+    /// `...` or `..=`
+    ClosedRange,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AssignmentOperator {
+    Assign,
+    Add,
+    Subtract,
+    Multiply,
+    Divide,
+    Remainder,
+}
@@ -1,4 +1,74 @@
-#[derive(Debug)]
-pub enum ParserError {
-    Unknown,
+use crate::lexer::{
+    error::{LexerError, TokenizeError},
+    token::{SourceLocation, TokenKind},
+};
+use std::ops::Range;
+
+#[derive(Debug, PartialEq)]
+pub struct ParserError<'a> {
+    // Boxed to keep the `Result`s the parser passes around small.
+    error: Box<SyntaxError<'a>>,
+    location: Range<SourceLocation>,
+}
+
+impl<'a> ParserError<'a> {
+    pub fn new(error: SyntaxError<'a>, start: SourceLocation, end: SourceLocation) -> Self {
+        Self {
+            error: Box::new(error),
+            location: start..end,
+        }
+    }
+
+    pub fn error(&self) -> &SyntaxError<'a> {
+        &self.error
+    }
+
+    pub fn location(&self) -> &Range<SourceLocation> {
+        &self.location
+    }
+}
+
+impl From<LexerError> for ParserError<'_> {
+    fn from(error: LexerError) -> Self {
+        let location = error.location().clone();
+        let error = SyntaxError::Lexer(error.error().clone());
+        ParserError::new(error, location.start, location.end)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum SyntaxError<'a> {
+    /// Found a token where `expected` must be.
+    ExpectedToken {
+        expected: Expected,
+        found: TokenKind<'a>,
+    },
+    /// The source ended where `expected` must be.
+    UnexpectedEof {
+        expected: Expected,
+    },
+    /// The `delimiter` at `opening` isn't closed before the end of the source
+    /// or a closing delimiter of another kind.
+    UnclosedDelimiter {
+        delimiter: TokenKind<'static>,
+        opening: Range<SourceLocation>,
+    },
+    /// An operator without associativity follows another one of the same
+    /// precedence, as in `a < b < c`.
+    ChainedNonAssociativeOperator(TokenKind<'a>),
+    /// The left side of an assignment is not a variable, member or index.
+    InvalidAssignmentTarget,
+    Lexer(TokenizeError),
+}
+
+/// What the parser was looking for when it reported a `SyntaxError`.
+#[derive(Debug, PartialEq)]
+pub enum Expected {
+    Token(TokenKind<'static>),
+    Identifier,
+    Expression,
+    Type,
+    Declaration,
+    /// A newline, `;` or `}` ending a statement.
+    StatementEnd,
 }
@@ -1,17 +1,1353 @@
-use crate::lexer::token::Token;
+use super::{
+    ast::{
+        Argument, AssignmentOperator, Attribute, BinaryOperator, Block, Closure, ClosureParameter,
+        ClosureSignature, Declaration, DeclarationKind, Else, Expression, ExpressionKind,
+        ExtendDeclaration, FunctionDeclaration, FunctionSignature, Identifier, If, Initializer,
+        Parameter, Program, Statement, StatementKind, StringSegment, StructDeclaration, Type,
+        TypeKind, UnaryOperator, VariableDeclaration,
+    },
+    error::{Expected, ParserError, SyntaxError},
+    precedence::{Associativity, Fixity, Precedence},
+};
+use crate::lexer::{
+    interner::Text,
+    lib::Lexer,
+    token::{Keyword, SourceLocation, Token, TokenKind},
+};
+use std::{collections::VecDeque, ops::Range};
 
-use super::error::ParserError;
+/// A recursive-descent parser that pulls tokens from the lexer as it needs
+/// them.
+///
+/// Statements end at a newline, a `;`, or the `}` closing their block. An
+/// expression continues on the next line when that line starts with an
+/// operator that can't start an expression, like `.` or `&&`. So `f` and
+/// `(x)` on separate lines are two statements, as are `a` and `-b`.
+///
+/// Expressions are parsed by precedence climbing over the `Precedence` table.
+/// A postfix `?` must directly follow its operand, as in `a?[0]`.
+///
+/// A `{` after an expression on the same line starts a trailing closure,
+/// except in the condition of an `if` or `while` and the sequence of a `for`,
+/// where it always starts the body. Trailing closures are allowed again inside
+/// parentheses, brackets and closures there, so `if (arr.first { $0 > 5 }) !=
+/// nil {` and `if arr.contains(where: { $0 > 5 }) {` work as intended.
+///
+/// After a syntax error, the parser skips to the start of the next statement
+/// and carries on, so `parse` reports every error along with what it could
+/// parse around them.
+pub struct Parser<'a> {
+    lexer: Lexer<'a>,
+    /// Tokens read from the lexer but not consumed yet.
+    lookahead: VecDeque<Token<'a>>,
+    previous_token_end: SourceLocation,
+    errors: Vec<ParserError<'a>>,
+    /// Whether a `{` can start a trailing closure, see `parse_condition`.
+    allows_trailing_closures: bool,
+}
+
+impl<'a> Parser<'a> {
+    pub fn new(lexer: Lexer<'a>) -> Self {
+        Self {
+            lexer: lexer.recovering(),
+            lookahead: VecDeque::new(),
+            previous_token_end: SourceLocation::new(1, 1, 0),
+            errors: Vec::new(),
+            allows_trailing_closures: true,
+        }
+    }
+
+    /// Parses the whole source, returning the statements that parsed along
+    /// with the lexer and parser errors, in source order.
+    pub fn parse(&mut self) -> (Program<'a>, Vec<ParserError<'a>>) {
+        let mut statements: Vec<Statement> = Vec::new();
+        while self.peek().is_some() {
+            if let Some(statement) = self.parse_statement_or_recover() {
+                statements.push(statement);
+            }
+        }
+
+        let mut errors: Vec<ParserError> = self
+            .lexer
+            .take_errors()
+            .into_iter()
+            .map(ParserError::from)
+            .collect();
+        errors.append(&mut self.errors);
+        errors.sort_by_key(|error| error.location().start.offset());
+
+        (Program { statements }, errors)
+    }
+
+    /// Parses a statement, or records the error and skips to the next
+    /// statement if it fails to parse.
+    fn parse_statement_or_recover(&mut self) -> Option<Statement<'a>> {
+        let start = self.next_start().offset();
+        match self.parse_statement() {
+            Ok(statement) => Some(statement),
+            Err(error) => {
+                self.recover(error, start);
+                None
+            }
+        }
+    }
+
+    /// Parses a statement. A statement that isn't followed by a statement end
+    /// is kept and the error recorded.
+    fn parse_statement(&mut self) -> Result<Statement<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        let kind = self.parse_statement_kind()?;
+        let location = self.location_from(start.clone());
+        if let Err(error) = self.expect_statement_end() {
+            self.recover(error, start.offset());
+        }
+        Ok(Statement { kind, location })
+    }
+
+    fn parse_statement_kind(&mut self) -> Result<StatementKind<'a>, ParserError<'a>> {
+        let kind = match self.peek_kind() {
+            Some(TokenKind::DocComment(_) | TokenKind::Attribute(_)) => {
+                StatementKind::Declaration(Box::new(self.parse_declaration()?))
+            }
+            Some(TokenKind::Keyword(keyword)) if is_start_of_declaration(keyword) => {
+                StatementKind::Declaration(Box::new(self.parse_declaration()?))
+            }
+            Some(TokenKind::Keyword(Keyword::Return)) => {
+                self.next();
+                let value = match self.is_at_statement_end() {
+                    true => None,
+                    false => Some(self.parse_expression()?),
+                };
+                StatementKind::Return(value)
+            }
+            Some(TokenKind::Keyword(Keyword::Throw)) => {
+                self.next();
+                StatementKind::Throw(self.parse_expression()?)
+            }
+            Some(TokenKind::Keyword(Keyword::Break)) => {
+                self.next();
+                StatementKind::Break
+            }
+            Some(TokenKind::Keyword(Keyword::Continue)) => {
+                self.next();
+                StatementKind::Continue
+            }
+            Some(TokenKind::Keyword(Keyword::If)) => StatementKind::If(self.parse_if()?),
+            Some(TokenKind::Keyword(Keyword::While)) => {
+                self.next();
+                let condition = self.parse_condition()?;
+                let body = self.parse_block()?;
+                StatementKind::While { condition, body }
+            }
+            Some(TokenKind::Keyword(Keyword::For)) => {
+                self.next();
+                let variable = self.expect_identifier()?;
+                self.expect(Keyword::In.into())?;
+                let iterable = self.parse_condition()?;
+                let body = self.parse_block()?;
+                StatementKind::For {
+                    variable,
+                    iterable,
+                    body,
+                }
+            }
+            _ => StatementKind::Expression(self.parse_expression()?),
+        };
+        Ok(kind)
+    }
+
+    fn parse_if(&mut self) -> Result<If<'a>, ParserError<'a>> {
+        self.expect(Keyword::If.into())?;
+        let condition = self.parse_condition()?;
+        let then_branch = self.parse_block()?;
+
+        let else_branch = if self.next_if(&Keyword::Else.into()).is_none() {
+            None
+        } else if self.is_next(&Keyword::If.into()) {
+            Some(Else::If(Box::new(self.parse_if()?)))
+        } else {
+            Some(Else::Block(self.parse_block()?))
+        };
+
+        Ok(If {
+            condition,
+            then_branch,
+            else_branch,
+        })
+    }
+
+    fn parse_block(&mut self) -> Result<Block<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        let opening = self.expect(TokenKind::OpenBrace)?.location().clone();
+        let statements = self.parse_statements_in_block(&opening);
+        Ok(Block {
+            statements,
+            location: self.location_from(start),
+        })
+    }
+
+    /// Parses the statements up to and including the `}` closing the block
+    /// opened at `opening`.
+    fn parse_statements_in_block(&mut self, opening: &Range<SourceLocation>) -> Vec<Statement<'a>> {
+        let mut statements: Vec<Statement> = Vec::new();
+        while !self.is_at_block_end(opening) {
+            if let Some(statement) = self.parse_statement_or_recover() {
+                statements.push(statement);
+            }
+        }
+        statements
+    }
+
+    /// Parses the block of a function or computed property. An expression
+    /// ending the block is its return value.
+    fn parse_body(&mut self) -> Result<Block<'a>, ParserError<'a>> {
+        let mut body = self.parse_block()?;
+        return_final_expression(&mut body.statements);
+        Ok(body)
+    }
+
+    /// Parses a declaration along with the doc comments and attributes in
+    /// front of it.
+    fn parse_declaration(&mut self) -> Result<Declaration<'a>, ParserError<'a>> {
+        let start = self.next_start();
+
+        let mut doc_comments: Vec<&str> = Vec::new();
+        while let Some(TokenKind::DocComment(text)) = self.peek_kind() {
+            doc_comments.push(text);
+            self.next();
+        }
+
+        let mut attributes: Vec<Attribute> = Vec::new();
+        while matches!(self.peek_kind(), Some(TokenKind::Attribute(_))) {
+            attributes.push(self.parse_attribute()?);
+        }
+
+        let kind = match self.peek_kind() {
+            Some(TokenKind::Keyword(Keyword::Let | Keyword::Var)) => {
+                DeclarationKind::Variable(self.parse_variable()?)
+            }
+            Some(TokenKind::Keyword(Keyword::Fn)) => {
+                DeclarationKind::Function(self.parse_function()?)
+            }
+            Some(TokenKind::Keyword(Keyword::Struct)) => {
+                DeclarationKind::Struct(self.parse_struct()?)
+            }
+            Some(TokenKind::Keyword(Keyword::Extend)) => {
+                DeclarationKind::Extend(self.parse_extend()?)
+            }
+            _ => return Err(self.unexpected(Expected::Declaration)),
+        };
+
+        Ok(Declaration {
+            kind,
+            doc_comments,
+            attributes,
+            location: self.location_from(start),
+        })
+    }
+
+    /// Parses an attribute such as `@inline` or `@deprecated("use g")`.
+    fn parse_attribute(&mut self) -> Result<Attribute<'a>, ParserError<'a>> {
+        let Some(token) = self.next() else {
+            return Err(self.unexpected(Expected::Declaration));
+        };
+        let location = token.location().clone();
+        let TokenKind::Attribute(name) = token.into_kind() else {
+            unreachable!("attributes are only parsed in front of an attribute token");
+        };
+        let name = Identifier {
+            name,
+            location: location.clone(),
+        };
+
+        let mut arguments: Vec<Argument> = Vec::new();
+        if self.is_next(&TokenKind::OpenParen) && !self.is_on_new_line() {
+            let opening = self.expect(TokenKind::OpenParen)?.location().clone();
+            arguments =
+                self.parse_delimited(opening, TokenKind::CloseParen, Self::parse_argument)?;
+        }
+
+        Ok(Attribute {
+            name,
+            arguments,
+            location: self.location_from(location.start),
+        })
+    }
+
+    fn parse_variable(&mut self) -> Result<VariableDeclaration<'a>, ParserError<'a>> {
+        let is_mutable = self.next_if(&Keyword::Var.into()).is_some();
+        if !is_mutable {
+            self.expect(Keyword::Let.into())?;
+        }
+        let name = self.expect_identifier()?;
+
+        let type_annotation = match self.next_if(&TokenKind::Colon) {
+            Some(_) => Some(self.parse_type()?),
+            None => None,
+        };
+
+        // Only properties with a type annotation can be computed, so that
+        // `let a = b { ... }` is never mistaken for one.
+        let is_computed = type_annotation.is_some()
+            && self.is_next(&TokenKind::OpenBrace)
+            && !self.is_on_new_line();
+
+        let initializer = if self.next_if(&TokenKind::Eq).is_some() {
+            Some(Initializer::Value(self.parse_expression()?))
+        } else if is_computed {
+            Some(Initializer::Computed(self.parse_body()?))
+        } else {
+            None
+        };
+
+        Ok(VariableDeclaration {
+            is_mutable,
+            name,
+            type_annotation,
+            initializer,
+        })
+    }
+
+    fn parse_function(&mut self) -> Result<FunctionDeclaration<'a>, ParserError<'a>> {
+        self.expect(Keyword::Fn.into())?;
+        let name = self.expect_identifier()?;
+        let signature = self.parse_function_signature()?;
+        let body = self.parse_body()?;
+        Ok(FunctionDeclaration {
+            name,
+            signature,
+            body,
+        })
+    }
+
+    fn parse_function_signature(&mut self) -> Result<FunctionSignature<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        let opening = self.expect(TokenKind::OpenParen)?.location().clone();
+        let parameters =
+            self.parse_delimited(opening, TokenKind::CloseParen, Self::parse_parameter)?;
+
+        let is_async = self.next_if(&Keyword::Async.into()).is_some();
+        let is_throwing = self.next_if(&Keyword::Throws.into()).is_some();
+        let return_type = match self.next_if(&TokenKind::Arrow) {
+            Some(_) => Some(self.parse_type()?),
+            None => None,
+        };
+
+        // Only throwing functions list their error types, as in
+        // `throws -> i32, SomeError`.
+        let mut error_types: Vec<Type> = Vec::new();
+        if is_throwing && return_type.is_some() {
+            while self.next_if(&TokenKind::Comma).is_some() {
+                error_types.push(self.parse_type()?);
+            }
+        }
+
+        Ok(FunctionSignature {
+            parameters,
+            is_async,
+            is_throwing,
+            return_type,
+            error_types,
+            location: self.location_from(start),
+        })
+    }
+
+    /// Parses a parameter such as `value: i32`, `to value: i32`,
+    /// `_ value: i32`, `by: i32 = 1` or `_ values: i32...`.
+    fn parse_parameter(&mut self) -> Result<Parameter<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        let first = self.expect_identifier()?;
+        let (label, name) = match self.next_identifier() {
+            Some(name) => (Some(first), name),
+            None => (Some(first.clone()), first),
+        };
+        let label = label.filter(|label| label.name != "_");
+
+        self.expect(TokenKind::Colon)?;
+        let type_annotation = self.parse_type()?;
+        let is_variadic = self.next_if(&TokenKind::Ellipsis).is_some();
+        let default_value = match self.next_if(&TokenKind::Eq) {
+            Some(_) => Some(self.parse_expression()?),
+            None => None,
+        };
+
+        Ok(Parameter {
+            label,
+            name,
+            type_annotation,
+            is_variadic,
+            default_value,
+            location: self.location_from(start),
+        })
+    }
+
+    fn parse_struct(&mut self) -> Result<StructDeclaration<'a>, ParserError<'a>> {
+        self.expect(Keyword::Struct.into())?;
+        let name = self.expect_identifier()?;
+        let members = self.parse_members()?;
+        Ok(StructDeclaration { name, members })
+    }
+
+    fn parse_extend(&mut self) -> Result<ExtendDeclaration<'a>, ParserError<'a>> {
+        self.expect(Keyword::Extend.into())?;
+        let extended_type = self.expect_identifier()?;
+        let members = self.parse_members()?;
+        Ok(ExtendDeclaration {
+            extended_type,
+            members,
+        })
+    }
+
+    /// Parses the declarations in the body of a `struct` or `extend`.
+    fn parse_members(&mut self) -> Result<Vec<Declaration<'a>>, ParserError<'a>> {
+        let opening = self.expect(TokenKind::OpenBrace)?.location().clone();
+
+        let mut members: Vec<Declaration> = Vec::new();
+        while !self.is_at_block_end(&opening) {
+            let start = self.next_start().offset();
+            match self.parse_declaration() {
+                Ok(member) => members.push(member),
+                Err(error) => {
+                    self.recover(error, start);
+                    continue;
+                }
+            }
+            if let Err(error) = self.expect_statement_end() {
+                self.recover(error, start);
+            }
+        }
+        Ok(members)
+    }
+
+    /// Consumes the `}` closing the block opened at `opening` if it's next.
+    /// Reaching the end of the source also ends the block, reporting it as
+    /// unclosed.
+    fn is_at_block_end(&mut self, opening: &Range<SourceLocation>) -> bool {
+        if self.next_if(&TokenKind::CloseBrace).is_some() {
+            return true;
+        }
+        if self.peek().is_some() {
+            return false;
+        }
+
+        let error = self.unclosed_delimiter(TokenKind::OpenBrace, opening.clone());
+        self.errors.push(error);
+        true
+    }
+
+    /// Parses a type such as `i32`, `i32[]?`, `(i32, String)`,
+    /// `(i32) async throws -> Bool` or `Map<String, i32>`.
+    fn parse_type(&mut self) -> Result<Type<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        let mut parsed_type = match self.peek_kind() {
+            Some(TokenKind::OpenParen) => self.parse_parenthesized_type()?,
+            _ => self.parse_named_type()?,
+        };
+
+        loop {
+            if self.is_on_new_line() {
+                break;
+            }
+            self.split_nil_coalescing();
+            let kind: fn(Box<Type<'a>>) -> TypeKind<'a> =
+                if self.next_if(&TokenKind::QuestionMark).is_some() {
+                    TypeKind::Optional
+                } else if self.is_next(&TokenKind::OpenBracket)
+                    && matches!(self.peek_nth_kind(1), Some(TokenKind::CloseBracket))
+                {
+                    self.next();
+                    self.next();
+                    TypeKind::Array
+                } else {
+                    break;
+                };
+            parsed_type = Type {
+                kind: kind(Box::new(parsed_type)),
+                location: self.location_from(start.clone()),
+            };
+        }
+        Ok(parsed_type)
+    }
+
+    /// Parses a type name along with its generic arguments, if any.
+    fn parse_named_type(&mut self) -> Result<Type<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        let Some(name) = self.next_identifier() else {
+            return Err(self.unexpected(Expected::Type));
+        };
+        let Some(opening) = self.next_if(&TokenKind::LessThan) else {
+            return Ok(Type {
+                location: name.location.clone(),
+                kind: TypeKind::Named(name),
+            });
+        };
+
+        let mut arguments: Vec<Type> = Vec::new();
+        loop {
+            arguments.push(self.parse_type()?);
+            if self.next_if(&TokenKind::Comma).is_none() {
+                break;
+            }
+        }
+        self.split_closing_angle_bracket();
+        self.expect_closing(opening.location().clone(), TokenKind::GreaterThan)?;
+
+        Ok(Type {
+            kind: TypeKind::Generic { name, arguments },
+            location: self.location_from(start),
+        })
+    }
+
+    /// Parses a type starting with `(`: a tuple, a function type, or a type
+    /// in parentheses such as the `(() -> i32)` in `(() -> i32)?`.
+    fn parse_parenthesized_type(&mut self) -> Result<Type<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        let opening = self.expect(TokenKind::OpenParen)?.location().clone();
+        let mut elements =
+            self.parse_delimited(opening, TokenKind::CloseParen, Self::parse_type)?;
+
+        let is_async = self.next_if(&Keyword::Async.into()).is_some();
+        let is_throwing = self.next_if(&Keyword::Throws.into()).is_some();
+        let kind = if is_async || is_throwing || self.is_next(&TokenKind::Arrow) {
+            self.expect(TokenKind::Arrow)?;
+            TypeKind::Function {
+                parameters: elements,
+                is_async,
+                is_throwing,
+                return_type: Box::new(self.parse_type()?),
+            }
+        } else if elements.len() == 1 {
+            return Ok(elements.remove(0));
+        } else {
+            TypeKind::Tuple(elements)
+        };
+
+        Ok(Type {
+            kind,
+            location: self.location_from(start),
+        })
+    }
+
+    fn parse_expression(&mut self) -> Result<Expression<'a>, ParserError<'a>> {
+        self.with_trailing_closures(true, |parser| parser.parse_expression_bp(0))
+    }
+
+    /// Parses the condition of an `if` or `while` or the sequence of a `for`,
+    /// where a `{` starts the body instead of a trailing closure. Expressions
+    /// nested in delimiters are parsed with `parse_expression`, which allows
+    /// trailing closures again.
+    fn parse_condition(&mut self) -> Result<Expression<'a>, ParserError<'a>> {
+        self.with_trailing_closures(false, |parser| parser.parse_expression_bp(0))
+    }
+
+    fn with_trailing_closures<T>(
+        &mut self,
+        allows_trailing_closures: bool,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParserError<'a>>,
+    ) -> Result<T, ParserError<'a>> {
+        let previous =
+            std::mem::replace(&mut self.allows_trailing_closures, allows_trailing_closures);
+        let result = parse(self);
+        self.allows_trailing_closures = previous;
+        result
+    }
+
+    /// Parses an expression made of operators that bind at least as strongly
+    /// as `min_binding_power`, as given by the `Precedence` table.
+    fn parse_expression_bp(
+        &mut self,
+        min_binding_power: u8,
+    ) -> Result<Expression<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        let mut expression = match self.peek_kind().and_then(unary_operator) {
+            Some(operator) => {
+                self.next();
+                let (_, right_power) = Precedence::Prefix.binding_power();
+                let operand = self.parse_expression_bp(right_power)?;
+                Expression {
+                    kind: ExpressionKind::Unary {
+                        operator,
+                        operand: Box::new(operand),
+                    },
+                    location: self.location_from(start.clone()),
+                }
+            }
+            None => self.parse_primary()?,
+        };
+
+        let mut previous_precedence: Option<Precedence> = None;
+        while let Some((fixity, precedence)) = self.peek_operator() {
+            let (left_power, right_power) = precedence.binding_power();
+            if left_power < min_binding_power {
+                break;
+            }
+            if previous_precedence == Some(precedence)
+                && precedence.associativity() == Associativity::None
+            {
+                let Some(token) = self.peek() else {
+                    return Err(self.unexpected(Expected::Expression));
+                };
+                let location = token.location().clone();
+                let error = SyntaxError::ChainedNonAssociativeOperator(token.kind().clone());
+                return Err(ParserError::new(error, location.start, location.end));
+            }
+            previous_precedence = Some(precedence);
+
+            let kind = match fixity {
+                Fixity::Postfix => self.parse_postfix_operator(expression)?,
+                _ => {
+                    let Some(operator) = self.next() else {
+                        return Err(self.unexpected(Expected::Expression));
+                    };
+                    let right = self.parse_expression_bp(right_power)?;
+                    infix_expression(operator.kind(), expression, right)
+                }
+            };
+            if let ExpressionKind::Assignment { target, .. } = &kind {
+                if !is_assignable(target) {
+                    let error = SyntaxError::InvalidAssignmentTarget;
+                    let location = target.location.clone();
+                    self.errors
+                        .push(ParserError::new(error, location.start, location.end));
+                }
+            }
+            expression = Expression {
+                kind,
+                location: self.location_from(start.clone()),
+            };
+        }
+        Ok(expression)
+    }
+
+    /// The fixity and precedence of the next token if it continues the
+    /// expression before it as an operator.
+    fn peek_operator(&mut self) -> Option<(Fixity, Precedence)> {
+        let is_on_new_line = self.is_on_new_line();
+        let is_attached = self.is_attached();
+        let allows_trailing_closures = self.allows_trailing_closures;
+        let kind = self.peek_kind()?;
+
+        let can_start_expression = Precedence::of(kind, Fixity::Prefix).is_some()
+            || matches!(
+                kind,
+                TokenKind::OpenParen | TokenKind::OpenBracket | TokenKind::OpenBrace
+            );
+        if is_on_new_line && can_start_expression {
+            return None;
+        }
+        if kind == &TokenKind::QuestionMark && !is_attached {
+            return None;
+        }
+        if kind == &TokenKind::OpenBrace && !allows_trailing_closures {
+            return None;
+        }
+
+        [Fixity::Postfix, Fixity::Infix]
+            .into_iter()
+            .find_map(|fixity| Some((fixity, Precedence::of(kind, fixity)?)))
+    }
+
+    /// Parses a call, trailing closure, index, member access or optional chain
+    /// applied to `operand`.
+    fn parse_postfix_operator(
+        &mut self,
+        operand: Expression<'a>,
+    ) -> Result<ExpressionKind<'a>, ParserError<'a>> {
+        if self.is_next(&TokenKind::OpenBrace) {
+            let trailing_closure = Some(Box::new(self.parse_closure()?));
+            // The closure is the last argument of a call in front of it, as in
+            // `f(x) { ... }`, and the only one otherwise.
+            let (callee, arguments) = match operand.kind {
+                ExpressionKind::Call {
+                    callee,
+                    arguments,
+                    trailing_closure: None,
+                } => (callee, arguments),
+                kind => {
+                    let callee = Expression {
+                        kind,
+                        location: operand.location,
+                    };
+                    (Box::new(callee), Vec::new())
+                }
+            };
+            return Ok(ExpressionKind::Call {
+                callee,
+                arguments,
+                trailing_closure,
+            });
+        }
+
+        let Some(token) = self.next() else {
+            return Err(self.unexpected(Expected::Expression));
+        };
+
+        let kind = match token.kind() {
+            TokenKind::OpenParen => ExpressionKind::Call {
+                callee: Box::new(operand),
+                arguments: self.parse_delimited(
+                    token.location().clone(),
+                    TokenKind::CloseParen,
+                    Self::parse_argument,
+                )?,
+                trailing_closure: None,
+            },
+            TokenKind::OpenBracket => {
+                let index = self.parse_expression()?;
+                self.expect_closing(token.location().clone(), TokenKind::CloseBracket)?;
+                ExpressionKind::Index {
+                    object: Box::new(operand),
+                    index: Box::new(index),
+                }
+            }
+            TokenKind::Period => ExpressionKind::MemberAccess {
+                object: Box::new(operand),
+                member: self.expect_identifier()?,
+            },
+            TokenKind::OptionalChain => {
+                // `?.` is a single token, so the `?` ends one char after it
+                // starts.
+                let question_mark_end = one_char_after(&token.location().start);
+                let location = operand.location.start.clone()..question_mark_end;
+                let object = Expression {
+                    kind: ExpressionKind::OptionalChain(Box::new(operand)),
+                    location,
+                };
+                ExpressionKind::MemberAccess {
+                    object: Box::new(object),
+                    member: self.expect_identifier()?,
+                }
+            }
+            TokenKind::QuestionMark => ExpressionKind::OptionalChain(Box::new(operand)),
+            _ => unreachable!("{:?} is not a postfix operator", token.kind()),
+        };
+        Ok(kind)
+    }
+
+    fn parse_primary(&mut self) -> Result<Expression<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        if let Some(identifier) = self.next_identifier() {
+            return Ok(Expression {
+                location: identifier.location.clone(),
+                kind: ExpressionKind::Identifier(identifier),
+            });
+        }
+
+        if self.is_next(&TokenKind::OpenBrace) {
+            let closure = self.parse_closure()?;
+            return Ok(Expression {
+                location: closure.location.clone(),
+                kind: ExpressionKind::Closure(Box::new(closure)),
+            });
+        }
 
-pub struct Parser {
-    _tokens: Vec<Token>,
+        // Check before consuming, so that a `}` closing the block isn't lost.
+        if !matches!(
+            self.peek_kind(),
+            Some(
+                TokenKind::Literal(_)
+                    | TokenKind::StringPart(_)
+                    | TokenKind::ClosureArg(_)
+                    | TokenKind::Keyword(Keyword::SelfValue | Keyword::Nil)
+                    | TokenKind::OpenParen
+                    | TokenKind::OpenBracket
+            )
+        ) {
+            return Err(self.unexpected(Expected::Expression));
+        }
+
+        let Some(token) = self.next() else {
+            return Err(self.unexpected(Expected::Expression));
+        };
+        let opening = token.location().clone();
+        let kind = match token.into_kind() {
+            TokenKind::Literal(literal) => ExpressionKind::Literal(literal),
+            TokenKind::StringPart(text) => self.parse_interpolated_string(text)?,
+            TokenKind::ClosureArg(index) => ExpressionKind::ClosureArg(index),
+            TokenKind::Keyword(Keyword::SelfValue) => ExpressionKind::SelfValue,
+            TokenKind::Keyword(Keyword::Nil) => ExpressionKind::Nil,
+            TokenKind::OpenParen => {
+                let expression = self.parse_expression()?;
+                self.expect_closing(opening, TokenKind::CloseParen)?;
+                expression.kind
+            }
+            TokenKind::OpenBracket => {
+                let elements =
+                    self.parse_delimited(opening, TokenKind::CloseBracket, Self::parse_expression)?;
+                ExpressionKind::Array(elements)
+            }
+            kind => unreachable!("{:?} can't start an expression", kind),
+        };
+
+        Ok(Expression {
+            kind,
+            location: self.location_from(start),
+        })
+    }
+
+    /// Parses a closure such as `{ $0 < 10 }`, `{ x, y in x + y }` or
+    /// `{ (x: i32) throws -> i32 in x + 1 }`. An expression ending the closure
+    /// is its return value.
+    fn parse_closure(&mut self) -> Result<Closure<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        let opening = self.expect(TokenKind::OpenBrace)?.location().clone();
+        let signature = match self.is_at_closure_signature() {
+            true => Some(self.parse_closure_signature()?),
+            false => None,
+        };
+
+        let mut body = self.parse_statements_in_block(&opening);
+        return_final_expression(&mut body);
+
+        Ok(Closure {
+            signature,
+            body,
+            location: self.location_from(start),
+        })
+    }
+
+    /// Whether the closure whose `{` was just consumed starts with a
+    /// signature. Only a signature is made of names, types and punctuation
+    /// followed by `in`.
+    fn is_at_closure_signature(&mut self) -> bool {
+        for n in 0.. {
+            match self.peek_nth_kind(n) {
+                Some(TokenKind::Keyword(Keyword::In)) => return true,
+                Some(TokenKind::Keyword(keyword)) if keyword.is_contextual() => {}
+                Some(
+                    TokenKind::Identifier(_)
+                    | TokenKind::Comma
+                    | TokenKind::Colon
+                    | TokenKind::OpenParen
+                    | TokenKind::CloseParen
+                    | TokenKind::OpenBracket
+                    | TokenKind::CloseBracket
+                    | TokenKind::LessThan
+                    | TokenKind::GreaterThan
+                    | TokenKind::ShiftRight
+                    | TokenKind::QuestionMark
+                    | TokenKind::Arrow,
+                ) => {}
+                _ => return false,
+            }
+        }
+        false
+    }
+
+    /// Parses a closure signature such as `x, y in` or
+    /// `(x: i32, y) async throws -> i32 in`.
+    fn parse_closure_signature(&mut self) -> Result<ClosureSignature<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        let parameters = match self.next_if(&TokenKind::OpenParen) {
+            Some(opening) => self.parse_delimited(
+                opening.location().clone(),
+                TokenKind::CloseParen,
+                Self::parse_closure_parameter,
+            )?,
+            // Without parentheses, a closure without parameters is written
+            // without `in` instead.
+            None => {
+                let mut parameters = vec![self.parse_closure_parameter()?];
+                while self.next_if(&TokenKind::Comma).is_some() {
+                    parameters.push(self.parse_closure_parameter()?);
+                }
+                parameters
+            }
+        };
+
+        let is_async = self.next_if(&Keyword::Async.into()).is_some();
+        let is_throwing = self.next_if(&Keyword::Throws.into()).is_some();
+        let return_type = match self.next_if(&TokenKind::Arrow) {
+            Some(_) => Some(self.parse_type()?),
+            None => None,
+        };
+        let location = self.location_from(start);
+        self.expect(Keyword::In.into())?;
+
+        Ok(ClosureSignature {
+            parameters,
+            is_async,
+            is_throwing,
+            return_type,
+            location,
+        })
+    }
+
+    /// Parses a closure parameter such as `x` or `x: i32`.
+    fn parse_closure_parameter(&mut self) -> Result<ClosureParameter<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        let name = self.expect_identifier()?;
+        let type_annotation = match self.next_if(&TokenKind::Colon) {
+            Some(_) => Some(self.parse_type()?),
+            None => None,
+        };
+
+        Ok(ClosureParameter {
+            name,
+            type_annotation,
+            location: self.location_from(start),
+        })
+    }
+
+    /// Parses the rest of an interpolated string whose first segment was
+    /// `first`. Empty segments are left out.
+    fn parse_interpolated_string(
+        &mut self,
+        first: Text<'a>,
+    ) -> Result<ExpressionKind<'a>, ParserError<'a>> {
+        let mut segments: Vec<StringSegment> = Vec::new();
+        let mut text = first;
+        loop {
+            if !text.is_empty() {
+                segments.push(StringSegment::Text(text));
+            }
+            if self.next_if(&TokenKind::InterpolationStart).is_none() {
+                return Ok(ExpressionKind::InterpolatedString(segments));
+            }
+
+            segments.push(StringSegment::Interpolation(self.parse_expression()?));
+            self.expect(TokenKind::InterpolationEnd)?;
+
+            // The lexer follows an interpolation with a string part, even an
+            // empty one, unless the string is invalid and already reported.
+            let Some(TokenKind::StringPart(_)) = self.peek_kind() else {
+                return Ok(ExpressionKind::InterpolatedString(segments));
+            };
+            let Some(TokenKind::StringPart(next_text)) = self.next().map(Token::into_kind) else {
+                unreachable!("the next token is a string part");
+            };
+            text = next_text;
+        }
+    }
+
+    /// Parses a call argument such as `"joe"` or `name: "joe"`.
+    fn parse_argument(&mut self) -> Result<Argument<'a>, ParserError<'a>> {
+        let start = self.next_start();
+        let label = match self.peek_nth_kind(1) {
+            Some(TokenKind::Colon) => {
+                let label = self.expect_identifier()?;
+                self.next();
+                Some(label)
+            }
+            _ => None,
+        };
+        let value = self.parse_expression()?;
+
+        Ok(Argument {
+            label,
+            value,
+            location: self.location_from(start),
+        })
+    }
+
+    /// Parses items separated by commas up to and including the `close` token
+    /// matching the delimiter at `opening`. A trailing comma is allowed.
+    fn parse_delimited<T>(
+        &mut self,
+        opening: Range<SourceLocation>,
+        close: TokenKind<'static>,
+        mut parse_item: impl FnMut(&mut Self) -> Result<T, ParserError<'a>>,
+    ) -> Result<Vec<T>, ParserError<'a>> {
+        let mut items: Vec<T> = Vec::new();
+        while self.next_if(&close).is_none() {
+            // Neither another closing delimiter nor a statement on the next
+            // line can be an item, so the delimiter was left unclosed.
+            let is_at_next_statement = self.is_on_new_line()
+                && matches!(
+                    self.peek_kind(),
+                    Some(TokenKind::Keyword(keyword)) if is_start_of_statement(keyword)
+                );
+            let is_at_closing_delimiter = matches!(
+                self.peek_kind(),
+                None | Some(
+                    TokenKind::CloseParen | TokenKind::CloseBracket | TokenKind::CloseBrace
+                )
+            );
+            if is_at_next_statement || is_at_closing_delimiter {
+                return Err(self.unclosed_delimiter(opening_delimiter(&close), opening));
+            }
+            items.push(parse_item(self)?);
+            if self.next_if(&TokenKind::Comma).is_none() {
+                self.expect_closing(opening, close)?;
+                break;
+            }
+        }
+        Ok(items)
+    }
+
+    /// Splits a `>>` or `>=` in front into a `>` and the rest, so that the `>`
+    /// can close generic arguments, as in `Array<Array<i32>>`.
+    fn split_closing_angle_bracket(&mut self) {
+        let rest = match self.peek_kind() {
+            Some(TokenKind::ShiftRight) => TokenKind::GreaterThan,
+            Some(TokenKind::GreaterThanEq) => TokenKind::Eq,
+            _ => return,
+        };
+        self.split_first_char(TokenKind::GreaterThan, rest);
+    }
+
+    /// Splits a `??` in front into two `?`, so that `i32??` is an optional
+    /// of an optional.
+    fn split_nil_coalescing(&mut self) {
+        if self.is_next(&TokenKind::NilCoalescing) {
+            self.split_first_char(TokenKind::QuestionMark, TokenKind::QuestionMark);
+        }
+    }
+
+    /// Replaces the next token with `first`, covering its first char, and
+    /// `rest`, covering the others.
+    fn split_first_char(&mut self, first: TokenKind<'a>, rest: TokenKind<'a>) {
+        let Some(token) = self.peek() else {
+            return;
+        };
+        let location = token.location().clone();
+        let middle = one_char_after(&location.start);
+        self.lookahead[0] = Token::new(first, location.start, middle.clone());
+        self.lookahead
+            .insert(1, Token::new(rest, middle, location.end));
+    }
+
+    /// Expects the `close` token matching the delimiter at `opening`. The
+    /// delimiter counts as unclosed if the source ends, another closing
+    /// delimiter follows or the next line starts instead.
+    fn expect_closing(
+        &mut self,
+        opening: Range<SourceLocation>,
+        close: TokenKind<'static>,
+    ) -> Result<Token<'a>, ParserError<'a>> {
+        if let Some(token) = self.next_if(&close) {
+            return Ok(token);
+        }
+        if self.is_on_new_line() {
+            return Err(self.unclosed_delimiter(opening_delimiter(&close), opening));
+        }
+        match self.peek_kind() {
+            None
+            | Some(TokenKind::CloseParen | TokenKind::CloseBracket | TokenKind::CloseBrace) => {
+                Err(self.unclosed_delimiter(opening_delimiter(&close), opening))
+            }
+            _ => Err(self.unexpected(Expected::Token(close))),
+        }
+    }
+
+    fn expect_statement_end(&mut self) -> Result<(), ParserError<'a>> {
+        if self.next_if(&TokenKind::Semicolon).is_some() || self.is_at_statement_end() {
+            return Ok(());
+        }
+        Err(self.unexpected(Expected::StatementEnd))
+    }
+
+    fn is_at_statement_end(&mut self) -> bool {
+        matches!(
+            self.peek_kind(),
+            None | Some(TokenKind::Semicolon | TokenKind::CloseBrace)
+        ) || self.is_on_new_line()
+    }
+
+    fn expect_identifier(&mut self) -> Result<Identifier<'a>, ParserError<'a>> {
+        match self.next_identifier() {
+            Some(identifier) => Ok(identifier),
+            None => Err(self.unexpected(Expected::Identifier)),
+        }
+    }
+
+    /// Reads an identifier if one is next. Contextual keywords are identifiers
+    /// everywhere they are not expected as keywords.
+    fn next_identifier(&mut self) -> Option<Identifier<'a>> {
+        let name = match self.peek_kind()? {
+            TokenKind::Identifier(name) => name.clone(),
+            TokenKind::Keyword(keyword) if keyword.is_contextual() => keyword.as_str().into(),
+            _ => return None,
+        };
+        let token = self.next()?;
+        Some(Identifier {
+            name,
+            location: token.location().clone(),
+        })
+    }
+
+    fn expect(&mut self, kind: TokenKind<'static>) -> Result<Token<'a>, ParserError<'a>> {
+        match self.next_if(&kind) {
+            Some(token) => Ok(token),
+            None => Err(self.unexpected(Expected::Token(kind))),
+        }
+    }
+
+    /// An error for the next token, or the end of the source, being there
+    /// instead of `expected`.
+    fn unexpected(&mut self, expected: Expected) -> ParserError<'a> {
+        let Some(token) = self.peek() else {
+            let end = self.previous_token_end.clone();
+            return ParserError::new(SyntaxError::UnexpectedEof { expected }, end.clone(), end);
+        };
+
+        let location = token.location().clone();
+        let found = token.kind().clone();
+        let error = SyntaxError::ExpectedToken { expected, found };
+        ParserError::new(error, location.start, location.end)
+    }
+
+    /// An error for the `delimiter` at `opening` not being closed where the
+    /// next token starts.
+    fn unclosed_delimiter(
+        &mut self,
+        delimiter: TokenKind<'static>,
+        opening: Range<SourceLocation>,
+    ) -> ParserError<'a> {
+        let location = self.next_start();
+        let error = SyntaxError::UnclosedDelimiter { delimiter, opening };
+        ParserError::new(error, location.clone(), location)
+    }
+
+    /// Records `error` for the statement starting at offset `start`, and skips
+    /// to where the next statement likely starts: past a `;`, or in front of a
+    /// new line, a declaration or a `}` closing the current block. Delimiters
+    /// in between are skipped in matching pairs.
+    fn recover(&mut self, error: ParserError<'a>, start: usize) {
+        // Invalid input already has a lexer error.
+        let is_lexer_error = matches!(
+            error.error(),
+            SyntaxError::ExpectedToken {
+                found: TokenKind::Error,
+                ..
+            }
+        );
+        if !is_lexer_error {
+            self.errors.push(error);
+        }
+
+        // Something that can't start a statement, like a stray `)`, is
+        // skipped so that parsing moves on.
+        if self.next_start().offset() == start {
+            self.next();
+        }
+
+        let mut depth: usize = 0;
+        while let Some(kind) = self.peek_kind() {
+            if depth == 0 {
+                let is_start_of_statement = match kind {
+                    TokenKind::Keyword(keyword) => is_start_of_declaration(keyword),
+                    TokenKind::DocComment(_) | TokenKind::Attribute(_) => true,
+                    _ => false,
+                };
+                if is_start_of_statement || kind == &TokenKind::CloseBrace || self.is_on_new_line()
+                {
+                    return;
+                }
+            }
+
+            match self.next().map(Token::into_kind) {
+                Some(TokenKind::OpenParen | TokenKind::OpenBracket | TokenKind::OpenBrace) => {
+                    depth += 1
+                }
+                Some(TokenKind::CloseParen | TokenKind::CloseBracket | TokenKind::CloseBrace) => {
+                    depth = depth.saturating_sub(1)
+                }
+                Some(TokenKind::Semicolon) if depth == 0 => return,
+                _ => {}
+            }
+        }
+    }
+
+    fn next_if(&mut self, kind: &TokenKind<'a>) -> Option<Token<'a>> {
+        if !self.is_next(kind) {
+            return None;
+        }
+        self.next()
+    }
+
+    fn is_next(&mut self, kind: &TokenKind<'a>) -> bool {
+        self.peek_kind() == Some(kind)
+    }
+
+    /// Whether the next token directly follows the previous one, without
+    /// whitespace or comments in between.
+    fn is_attached(&mut self) -> bool {
+        let previous_end = self.previous_token_end.offset();
+        self.peek()
+            .is_some_and(|token| token.location().start.offset() == previous_end)
+    }
+
+    /// Whether the next token is on a later line than the previous one.
+    fn is_on_new_line(&mut self) -> bool {
+        let previous_line = self.previous_token_end.line();
+        self.peek()
+            .is_some_and(|token| token.location().start.line() > previous_line)
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        self.peek();
+        let token = self.lookahead.pop_front()?;
+        self.previous_token_end = token.location().end.clone();
+        Some(token)
+    }
+
+    fn peek(&mut self) -> Option<&Token<'a>> {
+        self.peek_nth(0)
+    }
+
+    fn peek_kind(&mut self) -> Option<&TokenKind<'a>> {
+        self.peek_nth_kind(0)
+    }
+
+    fn peek_nth_kind(&mut self, n: usize) -> Option<&TokenKind<'a>> {
+        self.peek_nth(n).map(Token::kind)
+    }
+
+    fn peek_nth(&mut self, n: usize) -> Option<&Token<'a>> {
+        while self.lookahead.len() <= n {
+            // The lexer is recovering, so it never fails. The `Eof` token of a
+            // lossless lexer only holds trivia, and ends the source like `None`.
+            let Some(Ok(token)) = self.lexer.next() else {
+                break;
+            };
+            if token.kind() == &TokenKind::Eof {
+                break;
+            }
+            self.lookahead.push_back(token);
+        }
+        self.lookahead.get(n)
+    }
+
+    /// Where the next token starts, or where the source ends.
+    fn next_start(&mut self) -> SourceLocation {
+        match self.peek() {
+            Some(token) => token.location().start.clone(),
+            None => self.previous_token_end.clone(),
+        }
+    }
+
+    /// The location from `start` up to the end of the last consumed token.
+    fn location_from(&self, start: SourceLocation) -> Range<SourceLocation> {
+        start..self.previous_token_end.clone()
+    }
+}
+
+/// Turns an expression statement ending `statements` into an implicit return.
+fn return_final_expression(statements: &mut Vec<Statement>) {
+    let Some(statement) = statements.pop() else {
+        return;
+    };
+    let kind = match statement.kind {
+        StatementKind::Expression(expression) => StatementKind::ImplicitReturn(expression),
+        kind => kind,
+    };
+    statements.push(Statement { kind, ..statement });
+}
+
+/// Whether `expression` can be assigned to.
+fn is_assignable(expression: &Expression) -> bool {
+    matches!(
+        expression.kind,
+        ExpressionKind::Identifier(_)
+            | ExpressionKind::MemberAccess { .. }
+            | ExpressionKind::Index { .. }
+    )
+}
+
+/// The location of the char after the one at `location`, which must be on
+/// the same line and one byte long.
+fn one_char_after(location: &SourceLocation) -> SourceLocation {
+    SourceLocation::new(
+        location.line(),
+        location.column() + 1,
+        location.offset() + 1,
+    )
+    .in_file(location.file())
+}
+
+fn opening_delimiter(close: &TokenKind) -> TokenKind<'static> {
+    match close {
+        TokenKind::GreaterThan => TokenKind::LessThan,
+        TokenKind::CloseParen => TokenKind::OpenParen,
+        TokenKind::CloseBracket => TokenKind::OpenBracket,
+        TokenKind::CloseBrace => TokenKind::OpenBrace,
+        _ => unreachable!("{:?} is not a closing delimiter", close),
+    }
 }
 
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Self { _tokens: tokens }
+fn unary_operator(kind: &TokenKind) -> Option<UnaryOperator> {
+    match kind {
+        TokenKind::Negate => Some(UnaryOperator::Not),
+        TokenKind::Minus => Some(UnaryOperator::Negate),
+        TokenKind::Tilde => Some(UnaryOperator::BitwiseNot),
+        _ => None,
     }
+}
 
-    pub fn parse(&mut self) -> Result<(), ParserError> {
-        todo!();
+/// Builds the expression for the infix operator `kind`, which must have an
+/// infix precedence.
+fn infix_expression<'a>(
+    kind: &TokenKind,
+    left: Expression<'a>,
+    right: Expression<'a>,
+) -> ExpressionKind<'a> {
+    let assignment = match kind {
+        TokenKind::Eq => Some(AssignmentOperator::Assign),
+        TokenKind::PlusEq => Some(AssignmentOperator::Add),
+        TokenKind::MinusEq => Some(AssignmentOperator::Subtract),
+        TokenKind::AsteriskEq => Some(AssignmentOperator::Multiply),
+        TokenKind::SlashEq => Some(AssignmentOperator::Divide),
+        TokenKind::PercentEq => Some(AssignmentOperator::Remainder),
+        _ => None,
+    };
+    if let Some(operator) = assignment {
+        return ExpressionKind::Assignment {
+            operator,
+            target: Box::new(left),
+            value: Box::new(right),
+        };
     }
+
+    let operator = match kind {
+        TokenKind::Plus => BinaryOperator::Add,
+        TokenKind::Minus => BinaryOperator::Subtract,
+        TokenKind::Asterisk => BinaryOperator::Multiply,
+        TokenKind::Slash => BinaryOperator::Divide,
+        TokenKind::Percent => BinaryOperator::Remainder,
+        TokenKind::EqEq => BinaryOperator::Eq,
+        TokenKind::NotEq => BinaryOperator::NotEq,
+        TokenKind::LessThan => BinaryOperator::LessThan,
+        TokenKind::LessThanEq => BinaryOperator::LessThanEq,
+        TokenKind::GreaterThan => BinaryOperator::GreaterThan,
+        TokenKind::GreaterThanEq => BinaryOperator::GreaterThanEq,
+        TokenKind::AndAnd => BinaryOperator::And,
+        TokenKind::OrOr => BinaryOperator::Or,
+        TokenKind::Ampersand => BinaryOperator::BitwiseAnd,
+        TokenKind::Pipe => BinaryOperator::BitwiseOr,
+        TokenKind::Caret => BinaryOperator::BitwiseXor,
+        TokenKind::ShiftLeft => BinaryOperator::ShiftLeft,
+        TokenKind::ShiftRight => BinaryOperator::ShiftRight,
+        TokenKind::NilCoalescing => BinaryOperator::NilCoalescing,
+        TokenKind::HalfOpenRange => BinaryOperator::HalfOpenRange,
+        TokenKind::Ellipsis | TokenKind::InclusiveRange => BinaryOperator::ClosedRange,
+        _ => unreachable!("{:?} is not an infix operator", kind),
+    };
+    ExpressionKind::Binary {
+        operator,
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+fn is_start_of_declaration(keyword: &Keyword) -> bool {
+    matches!(
+        keyword,
+        Keyword::Let | Keyword::Var | Keyword::Fn | Keyword::Struct | Keyword::Extend
+    )
+}
+
+/// Whether `keyword` starts a statement that isn't an expression.
+fn is_start_of_statement(keyword: &Keyword) -> bool {
+    is_start_of_declaration(keyword)
+        || matches!(
+            keyword,
+            Keyword::Return
+                | Keyword::Throw
+                | Keyword::Break
+                | Keyword::Continue
+                | Keyword::If
+                | Keyword::While
+                | Keyword::For
+        )
 }
@@ -1,3 +1,5 @@
+pub mod ast;
 pub mod error;
 mod lib;
+pub mod precedence;
 pub use lib::Parser;
@@ -0,0 +1,128 @@
+use crate::lexer::token::TokenKind;
+
+/// How tightly operators bind, from loosest to tightest. The order follows
+/// Swift:
+///
+/// | Precedence       | Operators                          | Associativity |
+/// |------------------|------------------------------------|---------------|
+/// | `Assignment`     | `=` `+=` `-=` `*=` `/=` `%=`       | right         |
+/// | `Disjunction`    | `\|\|`                             | left          |
+/// | `Conjunction`    | `&&`                               | left          |
+/// | `Comparison`     | `==` `!=` `<` `<=` `>` `>=`        | none          |
+/// | `NilCoalescing`  | `??`                               | right         |
+/// | `Range`          | `..<` `...` `..=`                  | none          |
+/// | `Addition`       | `+` `-` `\|` `^`                   | left          |
+/// | `Multiplication` | `*` `/` `%` `&`                    | left          |
+/// | `Shift`          | `<<` `>>`                          | none          |
+/// | `Prefix`         | `-` `!` `~`                        | right         |
+/// | `Postfix`        | calls, trailing closures, `[]`,    | left          |
+/// |                  | `.`, `?.` and `?`                  |               |
+///
+/// Operators without associativity can't be chained, so `a < b < c` is an
+/// error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Precedence {
+    Assignment,
+    Disjunction,
+    Conjunction,
+    Comparison,
+    NilCoalescing,
+    Range,
+    Addition,
+    Multiplication,
+    Shift,
+    Prefix,
+    Postfix,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+    None,
+}
+
+/// Where an operator is written relative to its operands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fixity {
+    Prefix,
+    Infix,
+    Postfix,
+}
+
+impl Precedence {
+    /// The precedence of `kind` used as an operator with the given fixity, or
+    /// `None` if it isn't one.
+    pub fn of(kind: &TokenKind, fixity: Fixity) -> Option<Precedence> {
+        let precedence = match (fixity, kind) {
+            (Fixity::Prefix, TokenKind::Minus | TokenKind::Negate | TokenKind::Tilde) => {
+                Precedence::Prefix
+            }
+            (
+                Fixity::Postfix,
+                TokenKind::OpenParen
+                | TokenKind::OpenBracket
+                | TokenKind::OpenBrace
+                | TokenKind::Period
+                | TokenKind::OptionalChain
+                | TokenKind::QuestionMark,
+            ) => Precedence::Postfix,
+            (Fixity::Infix, kind) => match kind {
+                TokenKind::Eq
+                | TokenKind::PlusEq
+                | TokenKind::MinusEq
+                | TokenKind::AsteriskEq
+                | TokenKind::SlashEq
+                | TokenKind::PercentEq => Precedence::Assignment,
+                TokenKind::OrOr => Precedence::Disjunction,
+                TokenKind::AndAnd => Precedence::Conjunction,
+                TokenKind::EqEq
+                | TokenKind::NotEq
+                | TokenKind::LessThan
+                | TokenKind::LessThanEq
+                | TokenKind::GreaterThan
+                | TokenKind::GreaterThanEq => Precedence::Comparison,
+                TokenKind::NilCoalescing => Precedence::NilCoalescing,
+                TokenKind::HalfOpenRange | TokenKind::Ellipsis | TokenKind::InclusiveRange => {
+                    Precedence::Range
+                }
+                TokenKind::Plus | TokenKind::Minus | TokenKind::Pipe | TokenKind::Caret => {
+                    Precedence::Addition
+                }
+                TokenKind::Asterisk
+                | TokenKind::Slash
+                | TokenKind::Percent
+                | TokenKind::Ampersand => Precedence::Multiplication,
+                TokenKind::ShiftLeft | TokenKind::ShiftRight => Precedence::Shift,
+                _ => return None,
+            },
+            _ => return None,
+        };
+        Some(precedence)
+    }
+
+    pub fn associativity(self) -> Associativity {
+        match self {
+            Precedence::Assignment | Precedence::NilCoalescing | Precedence::Prefix => {
+                Associativity::Right
+            }
+            Precedence::Comparison | Precedence::Range | Precedence::Shift => Associativity::None,
+            Precedence::Disjunction
+            | Precedence::Conjunction
+            | Precedence::Addition
+            | Precedence::Multiplication
+            | Precedence::Postfix => Associativity::Left,
+        }
+    }
+
+    /// How strongly an operator with this precedence holds on to the operand
+    /// on its left and on its right. An operator takes an operand when its
+    /// binding power on that side is at least that of its neighbor.
+    pub fn binding_power(self) -> (u8, u8) {
+        let power = self as u8 * 2 + 1;
+        match self.associativity() {
+            Associativity::Right => (power + 1, power),
+            Associativity::Left | Associativity::None => (power, power + 1),
+        }
+    }
+}
@@ -1,9 +1,13 @@
-use std::iter::zip;
+use super::generate_sources;
+use std::{iter::zip, rc::Rc};
 
 use crate::lexer::{
     error::{LexerError, TokenizeError},
+    interner::Text,
     lib::Lexer,
-    token::{Keyword, Literal, SourceLocation, Token, TokenKind},
+    lint::{lint_identifiers, IdentifierLint, IdentifierLintKind},
+    source_map::{ColumnUnit, SourceFile, SourceMap},
+    token::{Keyword, Literal, NumberSuffix, SourceLocation, Token, TokenKind, Trivia},
 };
 
 #[test]
@@ -16,8 +20,8 @@ fn tokenizes_unterminated_str() {
 
     let expected = LexerError::new(
         TokenizeError::UnterminatedString,
-        SourceLocation::new(2, 22),
-        SourceLocation::new(3, 5),
+        SourceLocation::new(2, 22, 22),
+        SourceLocation::new(2, 26, 26),
     );
 
     assert_eq!(result, expected);
@@ -26,15 +30,15 @@ fn tokenizes_unterminated_str() {
 #[test]
 fn tokenizes_unknown_lexme() {
     let source = r#"
-        let amogus = ඞ
+        let amogus = €
     "#;
     let mut lexer = Lexer::new(source);
     let result = lexer.tokenize().unwrap_err();
 
     let expected = LexerError::new(
-        TokenizeError::UnknownLexme('ඞ'),
-        SourceLocation::new(2, 22),
-        SourceLocation::new(2, 23),
+        TokenizeError::UnknownLexme('€'),
+        SourceLocation::new(2, 22, 22),
+        SourceLocation::new(2, 23, 25),
     );
 
     assert_eq!(result, expected);
@@ -51,23 +55,23 @@ fn tokenizes_empty_str() {
     let expected = vec![
         Token::new(
             TokenKind::Keyword(Keyword::Let),
-            SourceLocation::new(2, 9),
-            SourceLocation::new(2, 12),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 12, 12),
         ),
         Token::new(
-            TokenKind::Identifier(String::from("message")),
-            SourceLocation::new(2, 13),
-            SourceLocation::new(2, 20),
+            TokenKind::Identifier("message".into()),
+            SourceLocation::new(2, 13, 13),
+            SourceLocation::new(2, 20, 20),
         ),
         Token::new(
             TokenKind::Eq,
-            SourceLocation::new(2, 21),
-            SourceLocation::new(2, 22),
+            SourceLocation::new(2, 21, 21),
+            SourceLocation::new(2, 22, 22),
         ),
         Token::new(
-            TokenKind::Literal(Literal::String(String::from(""))),
-            SourceLocation::new(2, 23),
-            SourceLocation::new(2, 25),
+            TokenKind::Literal(Literal::String("".into())),
+            SourceLocation::new(2, 23, 23),
+            SourceLocation::new(2, 25, 25),
         ),
     ];
 
@@ -84,19 +88,19 @@ fn tokenizes_greater_than_eq() {
 
     let expected = vec![
         Token::new(
-            Literal::Int(18).into(),
-            SourceLocation::new(2, 9),
-            SourceLocation::new(2, 11),
+            Literal::Int(18, None).into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 11, 11),
         ),
         Token::new(
             TokenKind::GreaterThanEq,
-            SourceLocation::new(2, 12),
-            SourceLocation::new(2, 14),
+            SourceLocation::new(2, 12, 12),
+            SourceLocation::new(2, 14, 14),
         ),
         Token::new(
-            Literal::Int(18).into(),
-            SourceLocation::new(2, 15),
-            SourceLocation::new(2, 17),
+            Literal::Int(18, None).into(),
+            SourceLocation::new(2, 15, 15),
+            SourceLocation::new(2, 17, 17),
         ),
     ];
 
@@ -113,19 +117,19 @@ fn tokenizes_less_than_eq() {
 
     let expected = vec![
         Token::new(
-            Literal::Int(14).into(),
-            SourceLocation::new(2, 9),
-            SourceLocation::new(2, 11),
+            Literal::Int(14, None).into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 11, 11),
         ),
         Token::new(
             TokenKind::LessThanEq,
-            SourceLocation::new(2, 12),
-            SourceLocation::new(2, 14),
+            SourceLocation::new(2, 12, 12),
+            SourceLocation::new(2, 14, 14),
         ),
         Token::new(
-            Literal::Int(18).into(),
-            SourceLocation::new(2, 15),
-            SourceLocation::new(2, 17),
+            Literal::Int(18, None).into(),
+            SourceLocation::new(2, 15, 15),
+            SourceLocation::new(2, 17, 17),
         ),
     ];
 
@@ -142,19 +146,19 @@ fn tokenizes_not_eq() {
 
     let expected = vec![
         Token::new(
-            Literal::Int(0).into(),
-            SourceLocation::new(2, 9),
-            SourceLocation::new(2, 10),
+            Literal::Int(0, None).into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 10, 10),
         ),
         Token::new(
             TokenKind::NotEq,
-            SourceLocation::new(2, 11),
-            SourceLocation::new(2, 13),
+            SourceLocation::new(2, 11, 11),
+            SourceLocation::new(2, 13, 13),
         ),
         Token::new(
-            Literal::Int(1).into(),
-            SourceLocation::new(2, 14),
-            SourceLocation::new(2, 15),
+            Literal::Int(1, None).into(),
+            SourceLocation::new(2, 14, 14),
+            SourceLocation::new(2, 15, 15),
         ),
     ];
 
@@ -171,19 +175,19 @@ fn tokenizes_eq_eq() {
 
     let expected = vec![
         Token::new(
-            Literal::Int(1).into(),
-            SourceLocation::new(2, 9),
-            SourceLocation::new(2, 10),
+            Literal::Int(1, None).into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 10, 10),
         ),
         Token::new(
             TokenKind::EqEq,
-            SourceLocation::new(2, 11),
-            SourceLocation::new(2, 13),
+            SourceLocation::new(2, 11, 11),
+            SourceLocation::new(2, 13, 13),
         ),
         Token::new(
-            Literal::Int(1).into(),
-            SourceLocation::new(2, 14),
-            SourceLocation::new(2, 15),
+            Literal::Int(1, None).into(),
+            SourceLocation::new(2, 14, 14),
+            SourceLocation::new(2, 15, 15),
         ),
     ];
 
@@ -205,44 +209,44 @@ fn tokenizes_skips_whitespace_and_comments() {
 
     let expected = vec![
         Token::new(
-            TokenKind::Identifier(String::from("print")),
-            SourceLocation::new(4, 9),
-            SourceLocation::new(4, 14),
+            TokenKind::Identifier("print".into()),
+            SourceLocation::new(4, 9, 128),
+            SourceLocation::new(4, 14, 133),
         ),
         Token::new(
             TokenKind::OpenParen,
-            SourceLocation::new(4, 14),
-            SourceLocation::new(4, 15),
+            SourceLocation::new(4, 14, 133),
+            SourceLocation::new(4, 15, 134),
         ),
         Token::new(
-            Literal::String(String::from("domo arigato, mr. roboto")).into(),
-            SourceLocation::new(4, 15),
-            SourceLocation::new(4, 41),
+            Literal::String("domo arigato, mr. roboto".into()).into(),
+            SourceLocation::new(4, 15, 134),
+            SourceLocation::new(4, 41, 160),
         ),
         Token::new(
             TokenKind::CloseParen,
-            SourceLocation::new(4, 41),
-            SourceLocation::new(4, 42),
+            SourceLocation::new(4, 41, 160),
+            SourceLocation::new(4, 42, 161),
         ),
         Token::new(
             Keyword::Let.into(),
-            SourceLocation::new(7, 9),
-            SourceLocation::new(7, 12),
+            SourceLocation::new(7, 9, 206),
+            SourceLocation::new(7, 12, 209),
         ),
         Token::new(
-            TokenKind::Identifier(String::from("forget")),
-            SourceLocation::new(7, 13),
-            SourceLocation::new(7, 19),
+            TokenKind::Identifier("forget".into()),
+            SourceLocation::new(7, 13, 210),
+            SourceLocation::new(7, 19, 216),
         ),
         Token::new(
             TokenKind::Eq,
-            SourceLocation::new(7, 20),
-            SourceLocation::new(7, 21),
+            SourceLocation::new(7, 20, 217),
+            SourceLocation::new(7, 21, 218),
         ),
         Token::new(
-            Literal::String(String::from("about it")).into(),
-            SourceLocation::new(7, 22),
-            SourceLocation::new(7, 32),
+            Literal::String("about it".into()).into(),
+            SourceLocation::new(7, 22, 219),
+            SourceLocation::new(7, 32, 229),
         ),
     ];
 
@@ -260,23 +264,23 @@ fn tokenizes_literal_bool() {
     let expected = vec![
         Token::new(
             Keyword::Let.into(),
-            SourceLocation::new(2, 9),
-            SourceLocation::new(2, 12),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 12, 12),
         ),
         Token::new(
-            TokenKind::Identifier(String::from("isAustinCool")),
-            SourceLocation::new(2, 13),
-            SourceLocation::new(2, 25),
+            TokenKind::Identifier("isAustinCool".into()),
+            SourceLocation::new(2, 13, 13),
+            SourceLocation::new(2, 25, 25),
         ),
         Token::new(
             TokenKind::Eq,
-            SourceLocation::new(2, 26),
-            SourceLocation::new(2, 27),
+            SourceLocation::new(2, 26, 26),
+            SourceLocation::new(2, 27, 27),
         ),
         Token::new(
             TokenKind::Literal(Literal::Bool(true)),
-            SourceLocation::new(2, 28),
-            SourceLocation::new(2, 32),
+            SourceLocation::new(2, 28, 28),
+            SourceLocation::new(2, 32, 32),
         ),
     ];
 
@@ -292,9 +296,9 @@ fn tokenizes_literal_str() {
     let result = lexer.tokenize().unwrap();
 
     let expected = vec![Token::new(
-        Literal::String(String::from("hello, world!")).into(),
-        SourceLocation::new(2, 9),
-        SourceLocation::new(2, 24),
+        Literal::String("hello, world!".into()).into(),
+        SourceLocation::new(2, 9, 9),
+        SourceLocation::new(2, 24, 24),
     )];
 
     assert_tokens_eq(result, expected);
@@ -311,23 +315,23 @@ fn tokenizes_literal_integer() {
     let expected = vec![
         Token::new(
             TokenKind::Keyword(Keyword::Let),
-            SourceLocation::new(2, 9),
-            SourceLocation::new(2, 12),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 12, 12),
         ),
         Token::new(
-            TokenKind::Identifier(String::from("age")),
-            SourceLocation::new(2, 13),
-            SourceLocation::new(2, 16),
+            TokenKind::Identifier("age".into()),
+            SourceLocation::new(2, 13, 13),
+            SourceLocation::new(2, 16, 16),
         ),
         Token::new(
             TokenKind::Eq,
-            SourceLocation::new(2, 17),
-            SourceLocation::new(2, 18),
+            SourceLocation::new(2, 17, 17),
+            SourceLocation::new(2, 18, 18),
         ),
         Token::new(
-            TokenKind::Literal(Literal::Int(24)),
-            SourceLocation::new(2, 19),
-            SourceLocation::new(2, 21),
+            TokenKind::Literal(Literal::Int(24, None)),
+            SourceLocation::new(2, 19, 19),
+            SourceLocation::new(2, 21, 21),
         ),
     ];
 
@@ -349,109 +353,1757 @@ fn tokenizes_snippet() {
     let expected: Vec<Token> = vec![
         Token::new(
             Keyword::Let.into(),
-            SourceLocation::new(2, 9),
-            SourceLocation::new(2, 12),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 12, 12),
         ),
         Token::new(
-            TokenKind::Identifier(String::from("str")),
-            SourceLocation::new(2, 13),
-            SourceLocation::new(2, 16),
+            TokenKind::Identifier("str".into()),
+            SourceLocation::new(2, 13, 13),
+            SourceLocation::new(2, 16, 16),
         ),
         Token::new(
             TokenKind::Eq,
-            SourceLocation::new(2, 17),
-            SourceLocation::new(2, 18),
+            SourceLocation::new(2, 17, 17),
+            SourceLocation::new(2, 18, 18),
         ),
         Token::new(
-            Literal::String(String::from("hello, world!")).into(),
-            SourceLocation::new(2, 19),
-            SourceLocation::new(2, 34),
+            Literal::String("hello, world!".into()).into(),
+            SourceLocation::new(2, 19, 19),
+            SourceLocation::new(2, 34, 34),
         ),
         Token::new(
-            TokenKind::Identifier(String::from("print")),
-            SourceLocation::new(3, 9),
-            SourceLocation::new(3, 14),
+            TokenKind::Identifier("print".into()),
+            SourceLocation::new(3, 9, 43),
+            SourceLocation::new(3, 14, 48),
         ),
         Token::new(
             TokenKind::OpenParen,
-            SourceLocation::new(3, 14),
-            SourceLocation::new(3, 15),
+            SourceLocation::new(3, 14, 48),
+            SourceLocation::new(3, 15, 49),
         ),
         Token::new(
-            TokenKind::Identifier(String::from("str")),
-            SourceLocation::new(3, 15),
-            SourceLocation::new(3, 18),
+            TokenKind::Identifier("str".into()),
+            SourceLocation::new(3, 15, 49),
+            SourceLocation::new(3, 18, 52),
         ),
         Token::new(
             TokenKind::CloseParen,
-            SourceLocation::new(3, 18),
-            SourceLocation::new(3, 19),
+            SourceLocation::new(3, 18, 52),
+            SourceLocation::new(3, 19, 53),
         ),
         Token::new(
             Keyword::Let.into(),
-            SourceLocation::new(5, 9),
-            SourceLocation::new(5, 12),
+            SourceLocation::new(5, 9, 63),
+            SourceLocation::new(5, 12, 66),
         ),
         Token::new(
-            TokenKind::Identifier(String::from("num")),
-            SourceLocation::new(5, 13),
-            SourceLocation::new(5, 16),
+            TokenKind::Identifier("num".into()),
+            SourceLocation::new(5, 13, 67),
+            SourceLocation::new(5, 16, 70),
         ),
         Token::new(
             TokenKind::Eq,
-            SourceLocation::new(5, 17),
-            SourceLocation::new(5, 18),
+            SourceLocation::new(5, 17, 71),
+            SourceLocation::new(5, 18, 72),
         ),
         Token::new(
-            Literal::Int(1).into(),
-            SourceLocation::new(5, 19),
-            SourceLocation::new(5, 20),
+            Literal::Int(1, None).into(),
+            SourceLocation::new(5, 19, 73),
+            SourceLocation::new(5, 20, 74),
         ),
         Token::new(
             TokenKind::Plus,
-            SourceLocation::new(5, 21),
-            SourceLocation::new(5, 22),
+            SourceLocation::new(5, 21, 75),
+            SourceLocation::new(5, 22, 76),
         ),
         Token::new(
-            Literal::Int(1).into(),
-            SourceLocation::new(5, 23),
-            SourceLocation::new(5, 24),
+            Literal::Int(1, None).into(),
+            SourceLocation::new(5, 23, 77),
+            SourceLocation::new(5, 24, 78),
         ),
         Token::new(
             Keyword::Let.into(),
-            SourceLocation::new(6, 9),
-            SourceLocation::new(6, 12),
+            SourceLocation::new(6, 9, 87),
+            SourceLocation::new(6, 12, 90),
         ),
         Token::new(
-            TokenKind::Identifier(String::from("isNumGreaterThanZero")),
-            SourceLocation::new(6, 13),
-            SourceLocation::new(6, 33),
+            TokenKind::Identifier("isNumGreaterThanZero".into()),
+            SourceLocation::new(6, 13, 91),
+            SourceLocation::new(6, 33, 111),
         ),
         Token::new(
             TokenKind::Eq,
-            SourceLocation::new(6, 34),
-            SourceLocation::new(6, 35),
+            SourceLocation::new(6, 34, 112),
+            SourceLocation::new(6, 35, 113),
         ),
         Token::new(
-            TokenKind::Identifier(String::from("num")),
-            SourceLocation::new(6, 36),
-            SourceLocation::new(6, 39),
+            TokenKind::Identifier("num".into()),
+            SourceLocation::new(6, 36, 114),
+            SourceLocation::new(6, 39, 117),
         ),
         Token::new(
             TokenKind::GreaterThan,
-            SourceLocation::new(6, 40),
-            SourceLocation::new(6, 41),
+            SourceLocation::new(6, 40, 118),
+            SourceLocation::new(6, 41, 119),
         ),
         Token::new(
-            Literal::Int(0).into(),
-            SourceLocation::new(6, 42),
-            SourceLocation::new(6, 43),
+            Literal::Int(0, None).into(),
+            SourceLocation::new(6, 42, 120),
+            SourceLocation::new(6, 43, 121),
         ),
     ];
 
     assert_tokens_eq(result, expected);
 }
 
+#[test]
+fn tokenizes_literal_double() {
+    let source = r#"
+        let ratio = 2.75
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            Keyword::Let.into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 12, 12),
+        ),
+        Token::new(
+            TokenKind::Identifier("ratio".into()),
+            SourceLocation::new(2, 13, 13),
+            SourceLocation::new(2, 18, 18),
+        ),
+        Token::new(
+            TokenKind::Eq,
+            SourceLocation::new(2, 19, 19),
+            SourceLocation::new(2, 20, 20),
+        ),
+        Token::new(
+            Literal::Double(2.75, None).into(),
+            SourceLocation::new(2, 21, 21),
+            SourceLocation::new(2, 25, 25),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_literal_double_with_exponent() {
+    let source = r#"
+        1e-9 6.02E23 2e+3
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            Literal::Double(1e-9, None).into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 13, 13),
+        ),
+        Token::new(
+            Literal::Double(6.02e23, None).into(),
+            SourceLocation::new(2, 14, 14),
+            SourceLocation::new(2, 21, 21),
+        ),
+        Token::new(
+            Literal::Double(2e3, None).into(),
+            SourceLocation::new(2, 22, 22),
+            SourceLocation::new(2, 26, 26),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_member_access_on_integer() {
+    let source = r#"
+        5.description
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            Literal::Int(5, None).into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 10, 10),
+        ),
+        Token::new(
+            TokenKind::Period,
+            SourceLocation::new(2, 10, 10),
+            SourceLocation::new(2, 11, 11),
+        ),
+        Token::new(
+            TokenKind::Identifier("description".into()),
+            SourceLocation::new(2, 11, 11),
+            SourceLocation::new(2, 22, 22),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_leading_and_trailing_period_as_separate_tokens() {
+    let source = r#"
+        .5 5.
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            TokenKind::Period,
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 10, 10),
+        ),
+        Token::new(
+            Literal::Int(5, None).into(),
+            SourceLocation::new(2, 10, 10),
+            SourceLocation::new(2, 11, 11),
+        ),
+        Token::new(
+            Literal::Int(5, None).into(),
+            SourceLocation::new(2, 12, 12),
+            SourceLocation::new(2, 13, 13),
+        ),
+        Token::new(
+            TokenKind::Period,
+            SourceLocation::new(2, 13, 13),
+            SourceLocation::new(2, 14, 14),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_malformed_float() {
+    let source = r#"
+        let x = 1.5e+
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::MalformedFloat(String::from("1.5e+")),
+        SourceLocation::new(2, 17, 17),
+        SourceLocation::new(2, 22, 22),
+    );
+
+    assert_eq!(result, expected);
+
+    let source = "1e400";
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::MalformedFloat(String::from("1e400")),
+        SourceLocation::new(1, 1, 0),
+        SourceLocation::new(1, 6, 5),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_wide_integer() {
+    let source = r#"
+        18446744073709551615
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![Token::new(
+        Literal::Int(u64::MAX as u128, None).into(),
+        SourceLocation::new(2, 9, 9),
+        SourceLocation::new(2, 29, 29),
+    )];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_integer_literal_overflow() {
+    let source = r#"
+        let big = 999999999999999999999999999999999999999999 + 1
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::IntegerLiteralOverflow(String::from(
+            "999999999999999999999999999999999999999999",
+        )),
+        SourceLocation::new(2, 19, 19),
+        SourceLocation::new(2, 61, 61),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_radix_integers() {
+    let source = r#"
+        0xFF 0o755 0b1010
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            Literal::Int(255, None).into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 13, 13),
+        ),
+        Token::new(
+            Literal::Int(493, None).into(),
+            SourceLocation::new(2, 14, 14),
+            SourceLocation::new(2, 19, 19),
+        ),
+        Token::new(
+            Literal::Int(10, None).into(),
+            SourceLocation::new(2, 20, 20),
+            SourceLocation::new(2, 26, 26),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_digit_separators() {
+    let source = r#"
+        1_000_000 0xFF_FF 1_000.000_1
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            Literal::Int(1_000_000, None).into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 18, 18),
+        ),
+        Token::new(
+            Literal::Int(0xFFFF, None).into(),
+            SourceLocation::new(2, 19, 19),
+            SourceLocation::new(2, 26, 26),
+        ),
+        Token::new(
+            Literal::Double(1_000.000_1, None).into(),
+            SourceLocation::new(2, 27, 27),
+            SourceLocation::new(2, 38, 38),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_number_suffixes() {
+    let source = r#"
+        10u32 2.5f32 0xFFu8 3f64
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            Literal::Int(10, Some(NumberSuffix::U32)).into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 14, 14),
+        ),
+        Token::new(
+            Literal::Double(2.5, Some(NumberSuffix::F32)).into(),
+            SourceLocation::new(2, 15, 15),
+            SourceLocation::new(2, 21, 21),
+        ),
+        Token::new(
+            Literal::Int(255, Some(NumberSuffix::U8)).into(),
+            SourceLocation::new(2, 22, 22),
+            SourceLocation::new(2, 28, 28),
+        ),
+        Token::new(
+            Literal::Double(3.0, Some(NumberSuffix::F64)).into(),
+            SourceLocation::new(2, 29, 29),
+            SourceLocation::new(2, 33, 33),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_invalid_digit_for_radix() {
+    let source = r#"
+        let mask = 0b10_1201
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::InvalidDigit {
+            digit: '2',
+            radix: 2,
+        },
+        SourceLocation::new(2, 26, 26),
+        SourceLocation::new(2, 27, 27),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_invalid_number_suffix() {
+    let source = r#"
+        2.5u32
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::InvalidNumberSuffix(String::from("u32")),
+        SourceLocation::new(2, 12, 12),
+        SourceLocation::new(2, 15, 15),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_str_escapes() {
+    let source = r#"
+        "say \"hi\"\n\t\\\r\0 \u{1F600}"
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![Token::new(
+        Literal::String("say \"hi\"\n\t\\\r\0 😀".into()).into(),
+        SourceLocation::new(2, 9, 9),
+        SourceLocation::new(2, 41, 41),
+    )];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_unknown_escape() {
+    let source = r#"
+        let path = "C:\Users"
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::UnknownEscape('U'),
+        SourceLocation::new(2, 23, 23),
+        SourceLocation::new(2, 25, 25),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_invalid_unicode_escape() {
+    let source = r#"
+        "surrogate \u{D800} and more"
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::InvalidUnicodeEscape,
+        SourceLocation::new(2, 20, 20),
+        SourceLocation::new(2, 28, 28),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_char_literals() {
+    let source = r#"
+        'a' '\n' '\'' '\u{2764}' '名'
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            Literal::Char('a').into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 12, 12),
+        ),
+        Token::new(
+            Literal::Char('\n').into(),
+            SourceLocation::new(2, 13, 13),
+            SourceLocation::new(2, 17, 17),
+        ),
+        Token::new(
+            Literal::Char('\'').into(),
+            SourceLocation::new(2, 18, 18),
+            SourceLocation::new(2, 22, 22),
+        ),
+        Token::new(
+            Literal::Char('\u{2764}').into(),
+            SourceLocation::new(2, 23, 23),
+            SourceLocation::new(2, 33, 33),
+        ),
+        Token::new(
+            Literal::Char('名').into(),
+            SourceLocation::new(2, 34, 34),
+            SourceLocation::new(2, 37, 39),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_invalid_char_literals() {
+    let source = r#"
+        '' 'ab' '\n\t' 'a
+    "#;
+    let mut lexer = Lexer::new(source);
+    let (_, errors) = lexer.tokenize_recovering();
+
+    let expected = vec![
+        LexerError::new(
+            TokenizeError::EmptyCharLiteral,
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 11, 11),
+        ),
+        LexerError::new(
+            TokenizeError::MultipleCharsInCharLiteral,
+            SourceLocation::new(2, 12, 12),
+            SourceLocation::new(2, 16, 16),
+        ),
+        LexerError::new(
+            TokenizeError::MultipleCharsInCharLiteral,
+            SourceLocation::new(2, 17, 17),
+            SourceLocation::new(2, 23, 23),
+        ),
+        LexerError::new(
+            TokenizeError::UnterminatedCharLiteral,
+            SourceLocation::new(2, 24, 24),
+            SourceLocation::new(2, 26, 26),
+        ),
+    ];
+
+    assert_eq!(errors, expected);
+}
+
+#[test]
+fn tokenizes_interpolated_str() {
+    let source = r#"
+        "\(name) is \(age)"
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            TokenKind::StringPart("".into()),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 10, 10),
+        ),
+        Token::new(
+            TokenKind::InterpolationStart,
+            SourceLocation::new(2, 10, 10),
+            SourceLocation::new(2, 12, 12),
+        ),
+        Token::new(
+            TokenKind::Identifier("name".into()),
+            SourceLocation::new(2, 12, 12),
+            SourceLocation::new(2, 16, 16),
+        ),
+        Token::new(
+            TokenKind::InterpolationEnd,
+            SourceLocation::new(2, 16, 16),
+            SourceLocation::new(2, 17, 17),
+        ),
+        Token::new(
+            TokenKind::StringPart(" is ".into()),
+            SourceLocation::new(2, 17, 17),
+            SourceLocation::new(2, 21, 21),
+        ),
+        Token::new(
+            TokenKind::InterpolationStart,
+            SourceLocation::new(2, 21, 21),
+            SourceLocation::new(2, 23, 23),
+        ),
+        Token::new(
+            TokenKind::Identifier("age".into()),
+            SourceLocation::new(2, 23, 23),
+            SourceLocation::new(2, 26, 26),
+        ),
+        Token::new(
+            TokenKind::InterpolationEnd,
+            SourceLocation::new(2, 26, 26),
+            SourceLocation::new(2, 27, 27),
+        ),
+        Token::new(
+            TokenKind::StringPart("".into()),
+            SourceLocation::new(2, 27, 27),
+            SourceLocation::new(2, 28, 28),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_interpolation_with_nested_parens_and_strings() {
+    let source = r#"
+        "a\(f((1), "b\(c)"))d"
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+    let kinds: Vec<TokenKind> = result.iter().map(|token| token.kind().clone()).collect();
+
+    let expected = vec![
+        TokenKind::StringPart("a".into()),
+        TokenKind::InterpolationStart,
+        TokenKind::Identifier("f".into()),
+        TokenKind::OpenParen,
+        TokenKind::OpenParen,
+        Literal::Int(1, None).into(),
+        TokenKind::CloseParen,
+        TokenKind::Comma,
+        TokenKind::StringPart("b".into()),
+        TokenKind::InterpolationStart,
+        TokenKind::Identifier("c".into()),
+        TokenKind::InterpolationEnd,
+        TokenKind::StringPart("".into()),
+        TokenKind::CloseParen,
+        TokenKind::InterpolationEnd,
+        TokenKind::StringPart("d".into()),
+    ];
+
+    assert_eq!(kinds, expected);
+}
+
+#[test]
+fn tokenizes_unterminated_interpolation() {
+    let source = r#"
+        "value: \(value"#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::UnterminatedString,
+        SourceLocation::new(2, 9, 9),
+        SourceLocation::new(2, 24, 24),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_multiline_str() {
+    let source = r#"
+        let poem = """
+            roses are "red"
+              violets are\tblue
+
+            \(name)
+            """
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            Keyword::Let.into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 12, 12),
+        ),
+        Token::new(
+            TokenKind::Identifier("poem".into()),
+            SourceLocation::new(2, 13, 13),
+            SourceLocation::new(2, 17, 17),
+        ),
+        Token::new(
+            TokenKind::Eq,
+            SourceLocation::new(2, 18, 18),
+            SourceLocation::new(2, 19, 19),
+        ),
+        Token::new(
+            TokenKind::StringPart("roses are \"red\"\n  violets are\tblue\n\n".into()),
+            SourceLocation::new(2, 20, 20),
+            SourceLocation::new(6, 13, 97),
+        ),
+        Token::new(
+            TokenKind::InterpolationStart,
+            SourceLocation::new(6, 13, 97),
+            SourceLocation::new(6, 15, 99),
+        ),
+        Token::new(
+            TokenKind::Identifier("name".into()),
+            SourceLocation::new(6, 15, 99),
+            SourceLocation::new(6, 19, 103),
+        ),
+        Token::new(
+            TokenKind::InterpolationEnd,
+            SourceLocation::new(6, 19, 103),
+            SourceLocation::new(6, 20, 104),
+        ),
+        Token::new(
+            TokenKind::StringPart("".into()),
+            SourceLocation::new(6, 20, 104),
+            SourceLocation::new(7, 16, 120),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_multiline_str_with_insufficient_indentation() {
+    let source = r#"
+        """
+            first
+          second
+            """
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::InsufficientIndentation,
+        SourceLocation::new(4, 1, 31),
+        SourceLocation::new(4, 11, 41),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_unterminated_multiline_str() {
+    let source = r#"
+        let text = """
+            never closed
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::UnterminatedMultilineString,
+        SourceLocation::new(2, 20, 20),
+        SourceLocation::new(4, 5, 53),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_raw_str() {
+    let source = r###"
+        #"C:\Users\"me""# ##"a "# b"##
+    "###;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            Literal::String(r#"C:\Users\"me""#.into()).into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 26, 26),
+        ),
+        Token::new(
+            Literal::String(r##"a "# b"##.into()).into(),
+            SourceLocation::new(2, 27, 27),
+            SourceLocation::new(2, 39, 39),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_unterminated_raw_str() {
+    let source = r##"
+        let raw = #"never closed"
+    "##;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::UnterminatedRawString,
+        SourceLocation::new(2, 19, 19),
+        SourceLocation::new(2, 34, 34),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_skips_nested_block_comments() {
+    let source = r#"
+        /* outer /* inner */ still outer */ let /**/ x /*** not docs */
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            Keyword::Let.into(),
+            SourceLocation::new(2, 45, 45),
+            SourceLocation::new(2, 48, 48),
+        ),
+        Token::new(
+            TokenKind::Identifier("x".into()),
+            SourceLocation::new(2, 54, 54),
+            SourceLocation::new(2, 55, 55),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_unterminated_block_comment() {
+    let source = r#"
+        let x = 1 /* /* */
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::UnterminatedBlockComment,
+        SourceLocation::new(2, 19, 19),
+        SourceLocation::new(3, 5, 32),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_doc_comments() {
+    let source = r#"
+        /// Adds one.
+        //// not docs
+        /** Really. */
+        fn
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            TokenKind::DocComment(" Adds one."),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 22, 22),
+        ),
+        Token::new(
+            TokenKind::DocComment(" Really. "),
+            SourceLocation::new(4, 9, 53),
+            SourceLocation::new(4, 23, 67),
+        ),
+        Token::new(
+            Keyword::Fn.into(),
+            SourceLocation::new(5, 9, 76),
+            SourceLocation::new(5, 11, 78),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_attributes() {
+    let source = r#"
+        @test @deprecated("use g") fn
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            TokenKind::Attribute("test".into()),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 14, 14),
+        ),
+        Token::new(
+            TokenKind::Attribute("deprecated".into()),
+            SourceLocation::new(2, 15, 15),
+            SourceLocation::new(2, 26, 26),
+        ),
+        Token::new(
+            TokenKind::OpenParen,
+            SourceLocation::new(2, 26, 26),
+            SourceLocation::new(2, 27, 27),
+        ),
+        Token::new(
+            Literal::String("use g".into()).into(),
+            SourceLocation::new(2, 27, 27),
+            SourceLocation::new(2, 34, 34),
+        ),
+        Token::new(
+            TokenKind::CloseParen,
+            SourceLocation::new(2, 34, 34),
+            SourceLocation::new(2, 35, 35),
+        ),
+        Token::new(
+            Keyword::Fn.into(),
+            SourceLocation::new(2, 36, 36),
+            SourceLocation::new(2, 38, 38),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_attribute_without_name() {
+    let source = r#"
+        @ inline
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::MissingAttributeName,
+        SourceLocation::new(2, 9, 9),
+        SourceLocation::new(2, 10, 10),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_operators() {
+    let source = r#"
+        && || -> % & | ^ ~ << >> += -= *= /= %=
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+    let kinds: Vec<TokenKind> = result.iter().map(|token| token.kind().clone()).collect();
+
+    let expected = vec![
+        TokenKind::AndAnd,
+        TokenKind::OrOr,
+        TokenKind::Arrow,
+        TokenKind::Percent,
+        TokenKind::Ampersand,
+        TokenKind::Pipe,
+        TokenKind::Caret,
+        TokenKind::Tilde,
+        TokenKind::ShiftLeft,
+        TokenKind::ShiftRight,
+        TokenKind::PlusEq,
+        TokenKind::MinusEq,
+        TokenKind::AsteriskEq,
+        TokenKind::SlashEq,
+        TokenKind::PercentEq,
+    ];
+
+    assert_eq!(kinds, expected);
+}
+
+#[test]
+fn tokenizes_operators_using_maximal_munch() {
+    let source = r#"
+        fn f()->i32 { a&&!b }
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            Keyword::Fn.into(),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 11, 11),
+        ),
+        Token::new(
+            TokenKind::Identifier("f".into()),
+            SourceLocation::new(2, 12, 12),
+            SourceLocation::new(2, 13, 13),
+        ),
+        Token::new(
+            TokenKind::OpenParen,
+            SourceLocation::new(2, 13, 13),
+            SourceLocation::new(2, 14, 14),
+        ),
+        Token::new(
+            TokenKind::CloseParen,
+            SourceLocation::new(2, 14, 14),
+            SourceLocation::new(2, 15, 15),
+        ),
+        Token::new(
+            TokenKind::Arrow,
+            SourceLocation::new(2, 15, 15),
+            SourceLocation::new(2, 17, 17),
+        ),
+        Token::new(
+            TokenKind::Identifier("i32".into()),
+            SourceLocation::new(2, 17, 17),
+            SourceLocation::new(2, 20, 20),
+        ),
+        Token::new(
+            TokenKind::OpenBrace,
+            SourceLocation::new(2, 21, 21),
+            SourceLocation::new(2, 22, 22),
+        ),
+        Token::new(
+            TokenKind::Identifier("a".into()),
+            SourceLocation::new(2, 23, 23),
+            SourceLocation::new(2, 24, 24),
+        ),
+        Token::new(
+            TokenKind::AndAnd,
+            SourceLocation::new(2, 24, 24),
+            SourceLocation::new(2, 26, 26),
+        ),
+        Token::new(
+            TokenKind::Negate,
+            SourceLocation::new(2, 26, 26),
+            SourceLocation::new(2, 27, 27),
+        ),
+        Token::new(
+            TokenKind::Identifier("b".into()),
+            SourceLocation::new(2, 27, 27),
+            SourceLocation::new(2, 28, 28),
+        ),
+        Token::new(
+            TokenKind::CloseBrace,
+            SourceLocation::new(2, 29, 29),
+            SourceLocation::new(2, 30, 30),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_range_and_optional_operators() {
+    let source = r#"
+        a ?? b 0..<10 1...5 1..=5 a?.b
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+    let kinds: Vec<TokenKind> = result.iter().map(|token| token.kind().clone()).collect();
+
+    let expected = vec![
+        TokenKind::Identifier("a".into()),
+        TokenKind::NilCoalescing,
+        TokenKind::Identifier("b".into()),
+        Literal::Int(0, None).into(),
+        TokenKind::HalfOpenRange,
+        Literal::Int(10, None).into(),
+        Literal::Int(1, None).into(),
+        TokenKind::Ellipsis,
+        Literal::Int(5, None).into(),
+        Literal::Int(1, None).into(),
+        TokenKind::InclusiveRange,
+        Literal::Int(5, None).into(),
+        TokenKind::Identifier("a".into()),
+        TokenKind::OptionalChain,
+        TokenKind::Identifier("b".into()),
+    ];
+
+    assert_eq!(kinds, expected);
+}
+
+#[test]
+fn tokenizes_ternary_with_implicit_member_as_question_mark() {
+    let source = r#"
+        a ? .b : c
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            TokenKind::Identifier("a".into()),
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 10, 10),
+        ),
+        Token::new(
+            TokenKind::QuestionMark,
+            SourceLocation::new(2, 11, 11),
+            SourceLocation::new(2, 12, 12),
+        ),
+        Token::new(
+            TokenKind::Period,
+            SourceLocation::new(2, 13, 13),
+            SourceLocation::new(2, 14, 14),
+        ),
+        Token::new(
+            TokenKind::Identifier("b".into()),
+            SourceLocation::new(2, 14, 14),
+            SourceLocation::new(2, 15, 15),
+        ),
+        Token::new(
+            TokenKind::Colon,
+            SourceLocation::new(2, 16, 16),
+            SourceLocation::new(2, 17, 17),
+        ),
+        Token::new(
+            TokenKind::Identifier("c".into()),
+            SourceLocation::new(2, 18, 18),
+            SourceLocation::new(2, 19, 19),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_spaced_question_mark_before_period_as_ternary() {
+    let source = r#"
+        a ?.b : c
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+    let kinds: Vec<TokenKind> = result.iter().map(|token| token.kind().clone()).collect();
+
+    let expected = vec![
+        TokenKind::Identifier("a".into()),
+        TokenKind::QuestionMark,
+        TokenKind::Period,
+        TokenKind::Identifier("b".into()),
+        TokenKind::Colon,
+        TokenKind::Identifier("c".into()),
+    ];
+
+    assert_eq!(kinds, expected);
+}
+
+#[test]
+fn tokenizes_closure_args() {
+    let source = r#"
+        { $0 < $12 }
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            TokenKind::OpenBrace,
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 10, 10),
+        ),
+        Token::new(
+            TokenKind::ClosureArg(0),
+            SourceLocation::new(2, 11, 11),
+            SourceLocation::new(2, 13, 13),
+        ),
+        Token::new(
+            TokenKind::LessThan,
+            SourceLocation::new(2, 14, 14),
+            SourceLocation::new(2, 15, 15),
+        ),
+        Token::new(
+            TokenKind::ClosureArg(12),
+            SourceLocation::new(2, 16, 16),
+            SourceLocation::new(2, 19, 19),
+        ),
+        Token::new(
+            TokenKind::CloseBrace,
+            SourceLocation::new(2, 20, 20),
+            SourceLocation::new(2, 21, 21),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_invalid_closure_arg() {
+    let source = r#"
+        { $value }
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::InvalidClosureArg,
+        SourceLocation::new(2, 11, 11),
+        SourceLocation::new(2, 12, 12),
+    );
+
+    assert_eq!(result, expected);
+
+    let source = "$0a";
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::InvalidClosureArg,
+        SourceLocation::new(1, 1, 0),
+        SourceLocation::new(1, 4, 3),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_bare_closure_arg() {
+    let source = "$";
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    let expected = LexerError::new(
+        TokenizeError::InvalidClosureArg,
+        SourceLocation::new(1, 1, 0),
+        SourceLocation::new(1, 2, 1),
+    );
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn tokenizes_keywords() {
+    let source = r#"
+        let var fn if else while for in match return break continue struct enum
+        trait extend import self nil throw try await async throws
+    "#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+    let keywords: Vec<Keyword> = result
+        .iter()
+        .map(|token| match token.kind() {
+            TokenKind::Keyword(keyword) => keyword.clone(),
+            kind => panic!("Expected keyword, found {:?}", kind),
+        })
+        .collect();
+
+    let expected = vec![
+        Keyword::Let,
+        Keyword::Var,
+        Keyword::Fn,
+        Keyword::If,
+        Keyword::Else,
+        Keyword::While,
+        Keyword::For,
+        Keyword::In,
+        Keyword::Match,
+        Keyword::Return,
+        Keyword::Break,
+        Keyword::Continue,
+        Keyword::Struct,
+        Keyword::Enum,
+        Keyword::Trait,
+        Keyword::Extend,
+        Keyword::Import,
+        Keyword::SelfValue,
+        Keyword::Nil,
+        Keyword::Throw,
+        Keyword::Try,
+        Keyword::Await,
+        Keyword::Async,
+        Keyword::Throws,
+    ];
+
+    assert_eq!(keywords, expected);
+    let contextual: Vec<&Keyword> = keywords.iter().filter(|k| k.is_contextual()).collect();
+    assert_eq!(contextual, vec![&Keyword::Async, &Keyword::Throws]);
+
+    for keyword in keywords {
+        assert_eq!(Keyword::from_str(keyword.as_str()), Some(keyword));
+    }
+}
+
+#[test]
+fn tokenizes_example_file() {
+    let source = include_str!("../../examples/example.shab");
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize();
+
+    assert!(result.is_ok(), "{:?}", result.unwrap_err());
+}
+
+#[test]
+fn tokenizes_recovering_reports_every_error() {
+    let source = r#"
+        let a = €
+        let b = "unterminated
+        let c = "bad \q escape" + 0b12
+    "#;
+    let mut lexer = Lexer::new(source);
+    let (tokens, errors) = lexer.tokenize_recovering();
+
+    let expected_errors = vec![
+        LexerError::new(
+            TokenizeError::UnknownLexme('€'),
+            SourceLocation::new(2, 17, 17),
+            SourceLocation::new(2, 18, 20),
+        ),
+        LexerError::new(
+            TokenizeError::UnterminatedString,
+            SourceLocation::new(3, 17, 37),
+            SourceLocation::new(3, 30, 50),
+        ),
+        LexerError::new(
+            TokenizeError::UnknownEscape('q'),
+            SourceLocation::new(4, 22, 72),
+            SourceLocation::new(4, 24, 74),
+        ),
+        LexerError::new(
+            TokenizeError::InvalidDigit {
+                digit: '2',
+                radix: 2,
+            },
+            SourceLocation::new(4, 38, 88),
+            SourceLocation::new(4, 39, 89),
+        ),
+    ];
+    assert_eq!(errors, expected_errors);
+
+    let kinds: Vec<TokenKind> = tokens.iter().map(|token| token.kind().clone()).collect();
+    let expected_kinds = vec![
+        Keyword::Let.into(),
+        TokenKind::Identifier("a".into()),
+        TokenKind::Eq,
+        TokenKind::Error,
+        Keyword::Let.into(),
+        TokenKind::Identifier("b".into()),
+        TokenKind::Eq,
+        TokenKind::Error,
+        Keyword::Let.into(),
+        TokenKind::Identifier("c".into()),
+        TokenKind::Eq,
+        Literal::String("bad \u{FFFD} escape".into()).into(),
+        TokenKind::Plus,
+        Literal::Int(1, None).into(),
+    ];
+    assert_eq!(kinds, expected_kinds);
+}
+
+#[test]
+fn tokenizes_recovering_after_str_ending_in_backslash() {
+    let source = "let a = \"abc\\\nlet b = 1";
+    let (tokens, errors) = Lexer::new(source).tokenize_recovering();
+
+    let expected_errors = vec![LexerError::new(
+        TokenizeError::UnterminatedString,
+        SourceLocation::new(1, 9, 8),
+        SourceLocation::new(1, 14, 13),
+    )];
+    assert_eq!(errors, expected_errors);
+
+    let kinds: Vec<TokenKind> = tokens.iter().map(|token| token.kind().clone()).collect();
+    let expected_kinds = vec![
+        Keyword::Let.into(),
+        TokenKind::Identifier("a".into()),
+        TokenKind::Eq,
+        TokenKind::Error,
+        Keyword::Let.into(),
+        TokenKind::Identifier("b".into()),
+        TokenKind::Eq,
+        Literal::Int(1, None).into(),
+    ];
+    assert_eq!(kinds, expected_kinds);
+}
+
+#[test]
+fn tokenizes_recovering_after_unclosed_interpolation() {
+    let source = "let s = \"a \\(x\nlet t = 2\nlet u = 3";
+    let (tokens, errors) = Lexer::new(source).tokenize_recovering();
+
+    let expected_errors = vec![LexerError::new(
+        TokenizeError::UnterminatedString,
+        SourceLocation::new(1, 9, 8),
+        SourceLocation::new(1, 15, 14),
+    )];
+    assert_eq!(errors, expected_errors);
+
+    let kinds: Vec<TokenKind> = tokens.iter().map(|token| token.kind().clone()).collect();
+    let expected_kinds = vec![
+        Keyword::Let.into(),
+        TokenKind::Identifier("s".into()),
+        TokenKind::Eq,
+        TokenKind::StringPart("a ".into()),
+        TokenKind::InterpolationStart,
+        TokenKind::Identifier("x".into()),
+        TokenKind::Error,
+        Keyword::Let.into(),
+        TokenKind::Identifier("t".into()),
+        TokenKind::Eq,
+        Literal::Int(2, None).into(),
+        Keyword::Let.into(),
+        TokenKind::Identifier("u".into()),
+        TokenKind::Eq,
+        Literal::Int(3, None).into(),
+    ];
+    assert_eq!(kinds, expected_kinds);
+}
+
+#[test]
+fn tokenizes_lossless_while_recovering() {
+    let source = "let s = 1\n  € x\n";
+    let (tokens, errors) = Lexer::new(source).lossless().tokenize_recovering();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(source_text(&tokens), source);
+    assert_eq!(tokens[4].kind(), &TokenKind::Error);
+    assert_eq!(tokens[4].source_text().unwrap(), "\n  €");
+}
+
+#[test]
+fn tokenizes_recovering_without_errors() {
+    let source = r#"
+        let a = 1
+    "#;
+    let (recovered_tokens, errors) = Lexer::new(source).tokenize_recovering();
+    let tokens = Lexer::new(source).tokenize().unwrap();
+
+    assert!(errors.is_empty());
+    assert_tokens_eq(recovered_tokens, tokens);
+}
+
+#[test]
+fn tokenizes_lazily_until_first_error() {
+    let source = "a \"b\nc";
+    let mut lexer = Lexer::new(source);
+
+    let token = lexer.next().unwrap().unwrap();
+    assert_eq!(token.kind(), &TokenKind::Identifier("a".into()));
+
+    let error = lexer.next().unwrap().unwrap_err();
+    assert_eq!(error.error(), &TokenizeError::UnterminatedString);
+
+    assert!(lexer.next().is_none());
+}
+
+#[test]
+fn tokenizes_lazily_while_recovering() {
+    let source = "a € b";
+    let mut lexer = Lexer::new(source).recovering();
+
+    let kinds: Vec<TokenKind> = lexer
+        .by_ref()
+        .map(|token| token.unwrap().kind().clone())
+        .collect();
+    let expected = vec![
+        TokenKind::Identifier("a".into()),
+        TokenKind::Error,
+        TokenKind::Identifier("b".into()),
+    ];
+    assert_eq!(kinds, expected);
+
+    let errors = lexer.take_errors();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].error(), &TokenizeError::UnknownLexme('€'));
+}
+
+#[test]
+fn tokenizes_borrowing_from_source() {
+    let source = r#"name "plain" "escaped\n" "\(name)!""#;
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let is_borrowed: Vec<bool> = result
+        .iter()
+        .filter_map(|token| match token.kind() {
+            TokenKind::Identifier(text)
+            | TokenKind::StringPart(text)
+            | TokenKind::Literal(Literal::String(text)) => Some(matches!(text, Text::Borrowed(_))),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(is_borrowed, vec![true, true, false, true, true, true]);
+}
+
+#[test]
+fn tokenizes_interning_text_that_differs_from_source() {
+    let source = "cafe\u{301} \"a\\n\" cafe\u{301} \"a\\n\" \"\\(a)a\\n\"";
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let texts: Vec<Rc<str>> = result
+        .iter()
+        .filter_map(|token| match token.kind() {
+            TokenKind::Identifier(Text::Interned(text))
+            | TokenKind::StringPart(Text::Interned(text))
+            | TokenKind::Literal(Literal::String(Text::Interned(text))) => Some(text.clone()),
+            _ => None,
+        })
+        .collect();
+
+    assert_eq!(texts.len(), 5);
+    assert!(Rc::ptr_eq(&texts[0], &texts[2]));
+    assert!(Rc::ptr_eq(&texts[1], &texts[3]));
+    assert!(Rc::ptr_eq(&texts[1], &texts[4]));
+}
+
+#[test]
+fn tokenizes_lossless_with_trivia() {
+    let source = "let x = 1 // one\n/* two */ x\n";
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize_lossless().unwrap();
+
+    let trivia: Vec<(Vec<Trivia>, Vec<Trivia>)> = result
+        .iter()
+        .map(|token| {
+            (
+                token.leading_trivia().to_vec(),
+                token.trailing_trivia().to_vec(),
+            )
+        })
+        .collect();
+    let space = Trivia::Whitespace(" ");
+
+    let expected = vec![
+        (vec![], vec![space.clone()]),
+        (vec![], vec![space.clone()]),
+        (vec![], vec![space.clone()]),
+        (vec![], vec![space.clone(), Trivia::LineComment("// one")]),
+        (
+            vec![
+                Trivia::Newline,
+                Trivia::BlockComment("/* two */"),
+                space.clone(),
+            ],
+            vec![],
+        ),
+        (vec![Trivia::Newline], vec![]),
+    ];
+
+    assert_eq!(trivia, expected);
+    assert_eq!(result.last().unwrap().kind(), &TokenKind::Eof);
+}
+
+#[test]
+fn tokenizes_lossless_round_trips_example_file() {
+    let source = include_str!("../../examples/example.shab");
+    assert_round_trips(source);
+}
+
+#[test]
+fn tokenizes_lossless_round_trips_generated_sources() {
+    let fragments = [
+        "let",
+        "fn",
+        "name",
+        "$0",
+        "1_000",
+        "0xFF",
+        "2.5e3",
+        "10u32",
+        "+=",
+        "->",
+        "?.",
+        "..<",
+        "(",
+        ")",
+        "{",
+        "}",
+        "\"plain\"",
+        "'c'",
+        "'\\n'",
+        "\"a \\(b + \"c\\(d)\") e\\n\"",
+        "#\"raw \"quoted\"\"#",
+        "\"\"\"\n    multi\n      line\n    \"\"\"",
+        "/// docs\n",
+        "@inline",
+        "/** block docs */",
+    ];
+    let separators = [
+        " ",
+        "\t",
+        "\n",
+        "\r\n",
+        "  // comment\n",
+        "/* block /* nested */ */",
+        "\n\n    ",
+    ];
+
+    for source in generate_sources(&fragments, &separators, 500) {
+        assert_round_trips(&source);
+    }
+
+    let invalid_fragments = [
+        "€",
+        "\"open",
+        "\"a \\(b",
+        "\"bad \\q\"",
+        "\"\"\"\n  under\n    \"\"\"",
+        "$x",
+        "$0a",
+        "0b12",
+        "1e400",
+        "'ab'",
+        "@",
+        "/* open",
+    ];
+    let fragments = [&fragments[..], &invalid_fragments].concat();
+    for source in generate_sources(&fragments, &separators, 500) {
+        let (tokens, _) = Lexer::new(&source).lossless().tokenize_recovering();
+        assert_eq!(source_text(&tokens), source);
+    }
+}
+
+#[test]
+fn tokenizes_byte_offsets_after_non_ascii_text() {
+    let source = "\"ñ😀\" x";
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            TokenKind::Literal(Literal::String("ñ😀".into())),
+            SourceLocation::new(1, 1, 0),
+            SourceLocation::new(1, 5, 8),
+        ),
+        Token::new(
+            TokenKind::Identifier("x".into()),
+            SourceLocation::new(1, 6, 9),
+            SourceLocation::new(1, 7, 10),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_with_file_id() {
+    let mut source_map = SourceMap::new();
+    source_map.add_file("main.shab", "import util");
+    let util = source_map.add_file("util.shab", "let x = 1\nfn y() {}");
+
+    let source = source_map.file(util).unwrap().text();
+    let mut lexer = Lexer::with_file(util, source);
+    let result = lexer.tokenize().unwrap();
+
+    let location = result[4].location();
+    assert_eq!(location.start.file(), util);
+    assert_eq!(source_map.slice(location), Some("fn"));
+}
+
+#[test]
+fn source_map_converts_offsets_to_line_and_column() {
+    let file = SourceFile::new("main.shab", "let a = 1\nlet 😀 = \"é\"\n");
+
+    let offset = file.text().find('=').unwrap();
+    assert_eq!(file.line_column(offset, ColumnUnit::Utf8), Some((1, 7)));
+
+    let offset = file.text().rfind('=').unwrap();
+    assert_eq!(file.line_column(offset, ColumnUnit::Utf8), Some((2, 10)));
+    assert_eq!(file.line_column(offset, ColumnUnit::Utf16), Some((2, 8)));
+    assert_eq!(file.line_column(offset, ColumnUnit::Char), Some((2, 7)));
+
+    assert_eq!(file.offset(2, 10, ColumnUnit::Utf8), Some(offset));
+    assert_eq!(file.offset(2, 8, ColumnUnit::Utf16), Some(offset));
+    assert_eq!(file.offset(2, 7, ColumnUnit::Char), Some(offset));
+
+    // Inside of the emoji, and past the end of the line.
+    assert_eq!(file.line_column(offset - 3, ColumnUnit::Char), None);
+    assert_eq!(file.offset(2, 6, ColumnUnit::Utf16), None);
+    assert_eq!(file.offset(1, 20, ColumnUnit::Char), None);
+    assert_eq!(file.line_count(), 3);
+}
+
+#[test]
+fn tokenizes_unicode_identifiers() {
+    let source = "let größe = 名前 + _ඞ2";
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap();
+
+    let expected = vec![
+        Token::new(
+            Keyword::Let.into(),
+            SourceLocation::new(1, 1, 0),
+            SourceLocation::new(1, 4, 3),
+        ),
+        Token::new(
+            TokenKind::Identifier("größe".into()),
+            SourceLocation::new(1, 5, 4),
+            SourceLocation::new(1, 10, 11),
+        ),
+        Token::new(
+            TokenKind::Eq,
+            SourceLocation::new(1, 11, 12),
+            SourceLocation::new(1, 12, 13),
+        ),
+        Token::new(
+            TokenKind::Identifier("名前".into()),
+            SourceLocation::new(1, 13, 14),
+            SourceLocation::new(1, 15, 20),
+        ),
+        Token::new(
+            TokenKind::Plus,
+            SourceLocation::new(1, 16, 21),
+            SourceLocation::new(1, 17, 22),
+        ),
+        Token::new(
+            TokenKind::Identifier("_ඞ2".into()),
+            SourceLocation::new(1, 18, 23),
+            SourceLocation::new(1, 21, 28),
+        ),
+    ];
+
+    assert_tokens_eq(result, expected);
+}
+
+#[test]
+fn tokenizes_identifiers_normalized_to_nfc() {
+    let decomposed = "cafe\u{301}";
+    let mut lexer = Lexer::new(decomposed);
+    let result = lexer.tokenize().unwrap();
+
+    assert_eq!(result[0].kind(), &TokenKind::Identifier("caf\u{e9}".into()));
+    assert_eq!(result[0].location().end, SourceLocation::new(1, 6, 6));
+}
+
+#[test]
+fn tokenizes_identifiers_without_xid_start_as_unknown() {
+    let source = "\u{301}a";
+    let mut lexer = Lexer::new(source);
+    let result = lexer.tokenize().unwrap_err();
+
+    assert_eq!(result.error(), &TokenizeError::UnknownLexme('\u{301}'));
+}
+
+#[test]
+fn lints_mixed_script_and_confusable_identifiers() {
+    let source = "let scope = pаypal + ѕсоре + rn + m";
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize().unwrap();
+    let result = lint_identifiers(&tokens);
+
+    let expected = vec![
+        IdentifierLint::new(
+            IdentifierLintKind::MixedScript("pаypal".into()),
+            SourceLocation::new(1, 13, 12),
+            SourceLocation::new(1, 19, 19),
+        ),
+        IdentifierLint::new(
+            IdentifierLintKind::Confusable {
+                identifier: "ѕсоре".into(),
+                other: "scope".into(),
+            },
+            SourceLocation::new(1, 22, 22),
+            SourceLocation::new(1, 27, 32),
+        ),
+    ];
+
+    assert_eq!(result, expected);
+}
+
+fn assert_round_trips(source: &str) {
+    let mut lexer = Lexer::new(source);
+    let tokens = lexer.tokenize_lossless().unwrap();
+
+    assert_eq!(source_text(&tokens), source);
+}
+
+fn source_text(tokens: &[Token]) -> String {
+    tokens
+        .iter()
+        .map(|token| token.source_text().unwrap())
+        .collect()
+}
+
 fn assert_tokens_eq(result: Vec<Token>, expected: Vec<Token>) {
     for (actual_token, expected_token) in zip(&result, &expected) {
         if actual_token != expected_token {
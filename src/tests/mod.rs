@@ -6,3 +6,30 @@ mod lexer;
 
 #[cfg(test)]
 mod parser;
+
+/// Generates `count` sources, each made of up to 20 random `fragments` that
+/// follow a random separator, sometimes with a separator at the end. The
+/// sequence is the same on every run.
+fn generate_sources(fragments: &[&str], separators: &[&str], count: usize) -> Vec<String> {
+    let mut seed: u64 = 0x5eed;
+    let mut next_random = |bound: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed as usize % bound
+    };
+
+    (0..count)
+        .map(|_| {
+            let mut source = String::new();
+            for _ in 0..next_random(20) {
+                source.push_str(separators[next_random(separators.len())]);
+                source.push_str(fragments[next_random(fragments.len())]);
+            }
+            if next_random(2) == 0 {
+                source.push_str(separators[next_random(separators.len())]);
+            }
+            source
+        })
+        .collect()
+}
@@ -1,4 +1,1574 @@
+use super::generate_sources;
+use crate::{
+    lexer::{
+        error::{LexerError, TokenizeError},
+        lib::Lexer,
+        token::{Keyword, Literal, SourceLocation, TokenKind},
+    },
+    parser::{
+        ast::{
+            Argument, AssignmentOperator, BinaryOperator, Closure, Declaration, DeclarationKind,
+            Else, Expression, ExpressionKind, Identifier, Initializer, Program, Statement,
+            StatementKind, StringSegment, Type, TypeKind, UnaryOperator, VariableDeclaration,
+        },
+        error::{Expected, ParserError, SyntaxError},
+        precedence::{Associativity, Fixity, Precedence},
+        Parser,
+    },
+};
+
+fn parse(source: &str) -> Program<'_> {
+    let (program, errors) = Parser::new(Lexer::new(source)).parse();
+    assert_eq!(errors, vec![], "{}", source);
+    program
+}
+
+fn parse_with_errors(source: &str) -> (Program<'_>, Vec<ParserError<'_>>) {
+    Parser::new(Lexer::new(source)).parse()
+}
+
+fn declaration<'a, 'b>(statement: &'b Statement<'a>) -> &'b Declaration<'a> {
+    let StatementKind::Declaration(declaration) = &statement.kind else {
+        panic!("expected a declaration, got {:?}", statement.kind);
+    };
+    declaration
+}
+
+fn expression<'a, 'b>(statement: &'b Statement<'a>) -> &'b ExpressionKind<'a> {
+    let StatementKind::Expression(expression) = &statement.kind else {
+        panic!("expected an expression, got {:?}", statement.kind);
+    };
+    &expression.kind
+}
+
+fn identifier_name<'b>(expression: &'b Expression) -> &'b str {
+    match &expression.kind {
+        ExpressionKind::Identifier(Identifier { name, .. }) => name,
+        kind => panic!("expected an identifier, got {:?}", kind),
+    }
+}
+
+/// Parses `source` as a single expression and writes it out with every
+/// operator parenthesized.
+fn parenthesize(source: &str) -> String {
+    let result = parse(source);
+    assert_eq!(result.statements.len(), 1, "{}", source);
+    let StatementKind::Expression(expression) = &result.statements[0].kind else {
+        panic!(
+            "expected an expression, got {:?}",
+            result.statements[0].kind
+        );
+    };
+    write_parenthesized(expression)
+}
+
+fn write_parenthesized(expression: &Expression) -> String {
+    match &expression.kind {
+        ExpressionKind::Literal(Literal::Int(value, _)) => value.to_string(),
+        ExpressionKind::Literal(Literal::String(text)) => format!("{:?}", text),
+        ExpressionKind::Identifier(identifier) => identifier.name.to_string(),
+        ExpressionKind::SelfValue => "self".to_string(),
+        ExpressionKind::Nil => "nil".to_string(),
+        ExpressionKind::ClosureArg(index) => format!("${}", index),
+        ExpressionKind::Unary { operator, operand } => {
+            let operator = match operator {
+                UnaryOperator::Not => "!",
+                UnaryOperator::Negate => "-",
+                UnaryOperator::BitwiseNot => "~",
+            };
+            format!("({}{})", operator, write_parenthesized(operand))
+        }
+        ExpressionKind::Binary {
+            operator,
+            left,
+            right,
+        } => format!(
+            "({} {:?} {})",
+            write_parenthesized(left),
+            operator,
+            write_parenthesized(right)
+        ),
+        ExpressionKind::Assignment {
+            operator,
+            target,
+            value,
+        } => format!(
+            "({} {:?}= {})",
+            write_parenthesized(target),
+            operator,
+            write_parenthesized(value)
+        ),
+        ExpressionKind::Call {
+            callee,
+            arguments,
+            trailing_closure,
+        } => {
+            let arguments: Vec<_> = arguments
+                .iter()
+                .map(|argument| write_parenthesized(&argument.value))
+                .collect();
+            let call = format!("{}({})", write_parenthesized(callee), arguments.join(", "));
+            match trailing_closure {
+                Some(closure) => format!("{} {}", call, write_closure(closure)),
+                None => call,
+            }
+        }
+        ExpressionKind::Closure(closure) => write_closure(closure),
+        ExpressionKind::MemberAccess { object, member } => {
+            format!("{}.{}", write_parenthesized(object), member.name)
+        }
+        ExpressionKind::Index { object, index } => {
+            format!(
+                "{}[{}]",
+                write_parenthesized(object),
+                write_parenthesized(index)
+            )
+        }
+        ExpressionKind::OptionalChain(operand) => format!("{}?", write_parenthesized(operand)),
+        kind => panic!("can't parenthesize {:?}", kind),
+    }
+}
+
+/// Writes out a closure with the names of its parameters and its
+/// parenthesized expression statements.
+fn write_closure(closure: &Closure) -> String {
+    let mut words = Vec::new();
+    if let Some(signature) = &closure.signature {
+        let names: Vec<_> = signature
+            .parameters
+            .iter()
+            .map(|parameter| parameter.name.name.as_ref())
+            .collect();
+        if !names.is_empty() {
+            words.push(names.join(", "));
+        }
+        words.push("in".to_string());
+    }
+    let statements: Vec<_> = closure
+        .body
+        .iter()
+        .map(|statement| match &statement.kind {
+            StatementKind::Expression(expression) | StatementKind::ImplicitReturn(expression) => {
+                write_parenthesized(expression)
+            }
+            kind => panic!("can't parenthesize {:?}", kind),
+        })
+        .collect();
+    if !statements.is_empty() {
+        words.push(statements.join("; "));
+    }
+    match words.is_empty() {
+        true => "{}".to_string(),
+        false => format!("{{ {} }}", words.join(" ")),
+    }
+}
+
+/// Parses `source_type` as the annotation of a `let` and writes it out with
+/// every postfix type parenthesized.
+fn write_type_annotation(source_type: &str) -> String {
+    let source = format!("let a: {}", source_type);
+    let result = parse(&source);
+    let DeclarationKind::Variable(VariableDeclaration {
+        type_annotation: Some(type_annotation),
+        ..
+    }) = &declaration(&result.statements[0]).kind
+    else {
+        panic!("expected a variable with a type annotation");
+    };
+    write_type(type_annotation)
+}
+
+fn write_type(parsed_type: &Type) -> String {
+    let write_types = |types: &[Type]| -> String {
+        let types: Vec<_> = types.iter().map(write_type).collect();
+        types.join(", ")
+    };
+    match &parsed_type.kind {
+        TypeKind::Named(name) => name.name.to_string(),
+        TypeKind::Generic { name, arguments } => {
+            format!("{}<{}>", name.name, write_types(arguments))
+        }
+        TypeKind::Optional(wrapped) => format!("({})?", write_type(wrapped)),
+        TypeKind::Array(element) => format!("({})[]", write_type(element)),
+        TypeKind::Tuple(elements) => format!("({})", write_types(elements)),
+        TypeKind::Function {
+            parameters,
+            is_async,
+            is_throwing,
+            return_type,
+        } => format!(
+            "fn({}){}{} -> {}",
+            write_types(parameters),
+            if *is_async { " async" } else { "" },
+            if *is_throwing { " throws" } else { "" },
+            write_type(return_type)
+        ),
+    }
+}
+
+#[test]
+fn parses_let_with_type_annotation() {
+    let source = "let a: i32 = 1";
+    let result = parse(source);
+
+    let expected = Program {
+        statements: vec![Statement {
+            kind: StatementKind::Declaration(Box::new(Declaration {
+                kind: DeclarationKind::Variable(VariableDeclaration {
+                    is_mutable: false,
+                    name: Identifier {
+                        name: "a".into(),
+                        location: SourceLocation::new(1, 5, 4)..SourceLocation::new(1, 6, 5),
+                    },
+                    type_annotation: Some(Type {
+                        kind: TypeKind::Named(Identifier {
+                            name: "i32".into(),
+                            location: SourceLocation::new(1, 8, 7)..SourceLocation::new(1, 11, 10),
+                        }),
+                        location: SourceLocation::new(1, 8, 7)..SourceLocation::new(1, 11, 10),
+                    }),
+                    initializer: Some(Initializer::Value(Expression {
+                        kind: ExpressionKind::Literal(Literal::Int(1, None)),
+                        location: SourceLocation::new(1, 14, 13)..SourceLocation::new(1, 15, 14),
+                    })),
+                }),
+                doc_comments: vec![],
+                attributes: vec![],
+                location: SourceLocation::new(1, 1, 0)..SourceLocation::new(1, 15, 14),
+            })),
+            location: SourceLocation::new(1, 1, 0)..SourceLocation::new(1, 15, 14),
+        }],
+    };
+
+    assert_eq!(result, expected);
+}
+
+#[test]
+fn parses_from_lossless_lexer() {
+    let source = "let a = 1 // one\n";
+    let (program, errors) = Parser::new(Lexer::new(source).lossless()).parse();
+
+    assert_eq!(errors, vec![]);
+    assert_eq!(program.statements.len(), 1);
+}
+
+#[test]
+fn parses_newline_terminated_statements() {
+    let source = r#"
+        let hello: String = "world"
+        println(hello)
+
+        var count = 0
+    "#;
+    let result = parse(source);
+
+    assert_eq!(result.statements.len(), 3);
+    assert!(matches!(
+        declaration(&result.statements[0]).kind,
+        DeclarationKind::Variable(VariableDeclaration {
+            is_mutable: false,
+            ..
+        })
+    ));
+    assert!(matches!(
+        expression(&result.statements[1]),
+        ExpressionKind::Call { .. }
+    ));
+    assert!(matches!(
+        declaration(&result.statements[2]).kind,
+        DeclarationKind::Variable(VariableDeclaration {
+            is_mutable: true,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn parses_semicolon_terminated_statements() {
+    let source = "let a = 1; let b = 2; f();";
+    let result = parse(source);
+
+    assert_eq!(result.statements.len(), 3);
+}
+
+#[test]
+fn reports_statements_without_separator() {
+    let source = "let a = 1 let b = 2";
+    let (program, errors) = parse_with_errors(source);
+
+    let expected = vec![ParserError::new(
+        SyntaxError::ExpectedToken {
+            expected: Expected::StatementEnd,
+            found: Keyword::Let.into(),
+        },
+        SourceLocation::new(1, 11, 10),
+        SourceLocation::new(1, 14, 13),
+    )];
+
+    assert_eq!(errors, expected);
+    assert_eq!(program.statements.len(), 2);
+}
+
+#[test]
+fn parses_call_on_next_line_as_new_statement() {
+    let source = r#"
+        f
+        (x)
+    "#;
+    let result = parse(source);
+
+    assert_eq!(result.statements.len(), 2);
+    assert_eq!(
+        *expression(&result.statements[0]),
+        ExpressionKind::Identifier(Identifier {
+            name: "f".into(),
+            location: SourceLocation::new(2, 9, 9)..SourceLocation::new(2, 10, 10),
+        })
+    );
+}
+
+#[test]
+fn parses_member_access_on_next_line() {
+    let source = r#"
+        person
+            .name
+            .count
+    "#;
+    let result = parse(source);
+
+    assert_eq!(result.statements.len(), 1);
+    let ExpressionKind::MemberAccess { object, member } = expression(&result.statements[0]) else {
+        panic!("expected a member access");
+    };
+    assert_eq!(member.name, "count");
+    assert!(matches!(
+        &object.kind,
+        ExpressionKind::MemberAccess { member, .. } if member.name == "name"
+    ));
+    assert_eq!(
+        result.statements[0].location,
+        SourceLocation::new(2, 9, 9)..SourceLocation::new(4, 19, 52)
+    );
+}
+
+#[test]
+fn parses_call_with_named_arguments() {
+    let source = r#"let me = Person(name: "joe", age: 30,)"#;
+    let result = parse(source);
+
+    let DeclarationKind::Variable(VariableDeclaration {
+        initializer: Some(Initializer::Value(value)),
+        ..
+    }) = &declaration(&result.statements[0]).kind
+    else {
+        panic!("expected a variable with a value");
+    };
+    let ExpressionKind::Call {
+        callee, arguments, ..
+    } = &value.kind
+    else {
+        panic!("expected a call");
+    };
+
+    assert_eq!(identifier_name(callee), "Person");
+    let labels: Vec<_> = arguments
+        .iter()
+        .map(|argument| argument.label.as_ref().map(|label| label.name.as_ref()))
+        .collect();
+    assert_eq!(labels, vec![Some("name"), Some("age")]);
+    assert_eq!(
+        arguments[1],
+        Argument {
+            label: Some(Identifier {
+                name: "age".into(),
+                location: SourceLocation::new(1, 30, 29)..SourceLocation::new(1, 33, 32),
+            }),
+            value: Expression {
+                kind: ExpressionKind::Literal(Literal::Int(30, None)),
+                location: SourceLocation::new(1, 35, 34)..SourceLocation::new(1, 37, 36),
+            },
+            location: SourceLocation::new(1, 30, 29)..SourceLocation::new(1, 37, 36),
+        }
+    );
+}
+
+#[test]
+fn parses_function_declaration() {
+    let source = r#"
+        fn move(_ value: i32, to target: Point, by: i32) async throws -> i32? {
+            return value
+        }
+    "#;
+    let result = parse(source);
+
+    let DeclarationKind::Function(function) = &declaration(&result.statements[0]).kind else {
+        panic!("expected a function");
+    };
+    assert_eq!(function.name.name, "move");
+
+    let parameters: Vec<_> = function
+        .signature
+        .parameters
+        .iter()
+        .map(|parameter| {
+            let label = parameter.label.as_ref().map(|label| label.name.as_ref());
+            (label, parameter.name.name.as_ref())
+        })
+        .collect();
+    assert_eq!(
+        parameters,
+        vec![(None, "value"), (Some("to"), "target"), (Some("by"), "by")]
+    );
+
+    assert!(function.signature.is_async);
+    assert!(function.signature.is_throwing);
+    assert!(matches!(
+        &function.signature.return_type,
+        Some(Type {
+            kind: TypeKind::Optional(_),
+            ..
+        })
+    ));
+    assert!(matches!(
+        &function.body.statements[..],
+        [Statement {
+            kind: StatementKind::Return(Some(_)),
+            ..
+        }]
+    ));
+}
+
+#[test]
+fn parses_parameter_defaults_and_variadics() {
+    let source = r#"
+        fn log(_ items: String..., separator: String = ", ", _: i32 = 1 + 1) {}
+    "#;
+    let result = parse(source);
+
+    let DeclarationKind::Function(function) = &declaration(&result.statements[0]).kind else {
+        panic!("expected a function");
+    };
+    let parameters = &function.signature.parameters;
+    assert_eq!(parameters.len(), 3);
+
+    assert_eq!(parameters[0].label, None);
+    assert!(parameters[0].is_variadic);
+    assert_eq!(parameters[0].default_value, None);
+
+    assert!(!parameters[1].is_variadic);
+    assert!(matches!(
+        &parameters[1].default_value,
+        Some(Expression {
+            kind: ExpressionKind::Literal(Literal::String(separator)),
+            ..
+        }) if separator == ", "
+    ));
+
+    assert_eq!(parameters[2].label, None);
+    assert_eq!(parameters[2].name.name, "_");
+    assert!(matches!(
+        &parameters[2].default_value,
+        Some(Expression {
+            kind: ExpressionKind::Binary { .. },
+            ..
+        })
+    ));
+    assert_eq!(
+        parameters[2].location,
+        SourceLocation::new(2, 62, 62)..SourceLocation::new(2, 76, 76)
+    );
+}
+
+#[test]
+fn parses_effects_and_error_types() {
+    let source = r#"
+        fn a1() async {}
+        fn t1() throws {}
+        fn at1() async throws -> i32, SomeError, OtherError? {}
+    "#;
+    let result = parse(source);
+
+    let signatures: Vec<_> = result
+        .statements
+        .iter()
+        .map(|statement| match &declaration(statement).kind {
+            DeclarationKind::Function(function) => &function.signature,
+            kind => panic!("expected a function, got {:?}", kind),
+        })
+        .collect();
+
+    assert!(signatures[0].is_async && !signatures[0].is_throwing);
+    assert!(!signatures[1].is_async && signatures[1].is_throwing);
+    assert!(signatures[1].error_types.is_empty());
+
+    assert!(signatures[2].is_async && signatures[2].is_throwing);
+    let error_types: Vec<_> = signatures[2]
+        .error_types
+        .iter()
+        .map(|error_type| &error_type.kind)
+        .collect();
+    assert!(matches!(
+        &error_types[..],
+        [TypeKind::Named(some), TypeKind::Optional(_)] if some.name == "SomeError"
+    ));
+    assert_eq!(
+        signatures[2].location,
+        SourceLocation::new(4, 15, 66)..SourceLocation::new(4, 61, 112)
+    );
+}
+
+#[test]
+fn reports_error_types_without_throws() {
+    let source = "fn f() -> i32, SomeError {}";
+    let (_, errors) = parse_with_errors(source);
+
+    let expected = vec![ParserError::new(
+        SyntaxError::ExpectedToken {
+            expected: Expected::Token(TokenKind::OpenBrace),
+            found: TokenKind::Comma,
+        },
+        SourceLocation::new(1, 14, 13),
+        SourceLocation::new(1, 15, 14),
+    )];
+
+    assert_eq!(errors, expected);
+}
+
+#[test]
+fn returns_final_expression_implicitly() {
+    let source = r#"
+        fn increment(_ value: i32) -> i32 {
+            print(value)
+            value + 1
+        }
+        fn f() {
+            return
+        }
+        fn g() {
+            let a = 1
+        }
+        fn h() {}
+    "#;
+    let result = parse(source);
+
+    let bodies: Vec<_> = result
+        .statements
+        .iter()
+        .map(|statement| match &declaration(statement).kind {
+            DeclarationKind::Function(function) => &function.body.statements,
+            kind => panic!("expected a function, got {:?}", kind),
+        })
+        .collect();
+
+    assert!(matches!(
+        &bodies[0][..],
+        [
+            Statement {
+                kind: StatementKind::Expression(_),
+                ..
+            },
+            Statement {
+                kind: StatementKind::ImplicitReturn(Expression {
+                    kind: ExpressionKind::Binary {
+                        operator: BinaryOperator::Add,
+                        ..
+                    },
+                    ..
+                }),
+                ..
+            }
+        ]
+    ));
+    assert_eq!(bodies[1][0].kind, StatementKind::Return(None));
+    assert!(matches!(bodies[2][0].kind, StatementKind::Declaration(_)));
+    assert!(bodies[3].is_empty());
+}
+
+#[test]
+fn parses_types() {
+    let cases = [
+        ("i32", "i32"),
+        ("i32[]?", "((i32)[])?"),
+        ("i32?[]", "((i32)?)[]"),
+        ("i32??", "((i32)?)?"),
+        ("i32???", "(((i32)?)?)?"),
+        ("i32?? = nil", "((i32)?)?"),
+        ("(i32, String)", "(i32, String)"),
+        ("()", "()"),
+        ("(i32)", "i32"),
+        ("(i32) async throws -> Bool", "fn(i32) async throws -> Bool"),
+        ("() -> ()", "fn() -> ()"),
+        ("(i32) -> i32?", "fn(i32) -> (i32)?"),
+        ("((i32) -> i32)?", "(fn(i32) -> i32)?"),
+        ("(i32, String)[]", "((i32, String))[]"),
+        ("Map<String, i32>", "Map<String, i32>"),
+        ("Map<String, i32[]>?", "(Map<String, (i32)[]>)?"),
+        ("Array<Array<i32>>", "Array<Array<i32>>"),
+        ("A<B<C<i32>>>", "A<B<C<i32>>>"),
+        ("Array<i32>= []", "Array<i32>"),
+        ("(throws) -> async", "fn(throws) -> async"),
+    ];
+
+    for (source_type, expected) in cases {
+        assert_eq!(
+            write_type_annotation(source_type),
+            expected,
+            "{}",
+            source_type
+        );
+    }
+}
+
+#[test]
+fn parses_type_locations() {
+    let source = "let a: Map<String, Array<i32>>?";
+    let result = parse(source);
+
+    let DeclarationKind::Variable(VariableDeclaration {
+        type_annotation: Some(optional),
+        ..
+    }) = &declaration(&result.statements[0]).kind
+    else {
+        panic!("expected a variable with a type annotation");
+    };
+    assert_eq!(
+        optional.location,
+        SourceLocation::new(1, 8, 7)..SourceLocation::new(1, 32, 31)
+    );
+
+    let TypeKind::Optional(map) = &optional.kind else {
+        panic!("expected an optional");
+    };
+    assert_eq!(
+        map.location,
+        SourceLocation::new(1, 8, 7)..SourceLocation::new(1, 31, 30)
+    );
+
+    let TypeKind::Generic { arguments, .. } = &map.kind else {
+        panic!("expected a generic type");
+    };
+    assert_eq!(
+        arguments[0].location,
+        SourceLocation::new(1, 12, 11)..SourceLocation::new(1, 18, 17)
+    );
+    assert_eq!(
+        arguments[1].location,
+        SourceLocation::new(1, 20, 19)..SourceLocation::new(1, 30, 29)
+    );
+}
+
+#[test]
+fn parses_types_in_declarations() {
+    let source = r#"
+        fn apply(_ f: (i32) -> i32, to values: i32[]) -> Map<String, i32>? {}
+        struct Pairs {
+            let pairs: (i32, i32)[]
+        }
+    "#;
+    let result = parse(source);
+
+    let DeclarationKind::Function(function) = &declaration(&result.statements[0]).kind else {
+        panic!("expected a function");
+    };
+    let parameter_types: Vec<_> = function
+        .signature
+        .parameters
+        .iter()
+        .map(|parameter| write_type(&parameter.type_annotation))
+        .collect();
+    assert_eq!(parameter_types, vec!["fn(i32) -> i32", "(i32)[]"]);
+    let return_type = function.signature.return_type.as_ref().unwrap();
+    assert_eq!(write_type(return_type), "(Map<String, i32>)?");
+
+    let DeclarationKind::Struct(pairs) = &declaration(&result.statements[1]).kind else {
+        panic!("expected a struct");
+    };
+    let DeclarationKind::Variable(VariableDeclaration {
+        type_annotation: Some(field_type),
+        ..
+    }) = &pairs.members[0].kind
+    else {
+        panic!("expected a field");
+    };
+    assert_eq!(write_type(field_type), "((i32, i32))[]");
+}
+
+#[test]
+fn parses_array_literal_on_line_after_type() {
+    let source = r#"
+        let a: i32
+        []
+    "#;
+    let result = parse(source);
+
+    assert_eq!(result.statements.len(), 2);
+}
+
+#[test]
+fn reports_invalid_types() {
+    let source = r#"
+        let a: Map<String
+        let b: 1
+    "#;
+    let (_, errors) = parse_with_errors(source);
+
+    let expected = vec![
+        ParserError::new(
+            SyntaxError::UnclosedDelimiter {
+                delimiter: TokenKind::LessThan,
+                opening: SourceLocation::new(2, 19, 19)..SourceLocation::new(2, 20, 20),
+            },
+            SourceLocation::new(3, 9, 35),
+            SourceLocation::new(3, 9, 35),
+        ),
+        ParserError::new(
+            SyntaxError::ExpectedToken {
+                expected: Expected::Type,
+                found: TokenKind::Literal(Literal::Int(1, None)),
+            },
+            SourceLocation::new(3, 16, 42),
+            SourceLocation::new(3, 17, 43),
+        ),
+    ];
+
+    assert_eq!(errors, expected);
+}
+
+#[test]
+fn parses_struct_declaration() {
+    let source = r#"
+        struct Person {
+            let name: string
+            var age: u32; let tags: string[]
+        }
+    "#;
+    let result = parse(source);
+
+    let DeclarationKind::Struct(person) = &declaration(&result.statements[0]).kind else {
+        panic!("expected a struct");
+    };
+    assert_eq!(person.name.name, "Person");
+
+    let members: Vec<_> = person
+        .members
+        .iter()
+        .map(|member| match &member.kind {
+            DeclarationKind::Variable(variable) => variable.name.name.as_ref(),
+            kind => panic!("expected a field, got {:?}", kind),
+        })
+        .collect();
+    assert_eq!(members, vec!["name", "age", "tags"]);
+}
+
+#[test]
+fn parses_extend_with_computed_property() {
+    let source = r#"
+        extend Person {
+            fn description() -> string {
+                self.name
+            }
+
+            let displayName: String {
+                "\(self.name)!"
+            }
+        }
+    "#;
+    let result = parse(source);
+
+    let DeclarationKind::Extend(extension) = &declaration(&result.statements[0]).kind else {
+        panic!("expected an extend block");
+    };
+    assert_eq!(extension.extended_type.name, "Person");
+    assert!(matches!(
+        extension.members[0].kind,
+        DeclarationKind::Function(_)
+    ));
+
+    let DeclarationKind::Variable(VariableDeclaration {
+        initializer: Some(Initializer::Computed(block)),
+        ..
+    }) = &extension.members[1].kind
+    else {
+        panic!("expected a computed property");
+    };
+    let StatementKind::ImplicitReturn(Expression {
+        kind: ExpressionKind::InterpolatedString(segments),
+        ..
+    }) = &block.statements[0].kind
+    else {
+        panic!("expected an interpolated string");
+    };
+    assert!(matches!(
+        &segments[..],
+        [StringSegment::Interpolation(_), StringSegment::Text(text)] if text == "!"
+    ));
+}
+
+#[test]
+fn parses_block_on_next_line_after_let_as_new_statement() {
+    let source = r#"
+        let a: i32
+        f()
+    "#;
+    let result = parse(source);
+
+    assert_eq!(result.statements.len(), 2);
+    assert!(matches!(
+        declaration(&result.statements[0]).kind,
+        DeclarationKind::Variable(VariableDeclaration {
+            initializer: None,
+            ..
+        })
+    ));
+}
+
+#[test]
+fn attaches_doc_comments_and_attributes_to_declarations() {
+    let source = r#"
+        /// Adds one.
+        /// Really.
+        @inline
+        @deprecated("use add")
+        fn increment() {}
+    "#;
+    let result = parse(source);
+
+    assert_eq!(result.statements.len(), 1);
+    let increment = declaration(&result.statements[0]);
+    assert_eq!(increment.doc_comments, vec![" Adds one.", " Really."]);
+
+    let attributes: Vec<_> = increment
+        .attributes
+        .iter()
+        .map(|attribute| (attribute.name.name.as_ref(), attribute.arguments.len()))
+        .collect();
+    assert_eq!(attributes, vec![("inline", 0), ("deprecated", 1)]);
+    assert_eq!(
+        increment.location,
+        SourceLocation::new(2, 9, 9)..SourceLocation::new(6, 26, 115)
+    );
+}
+
+#[test]
+fn parses_control_flow() {
+    let source = r#"
+        if a {
+            break
+        } else if b {
+            continue
+        } else {
+            return
+        }
+        while running { step() }
+        for item in items { throw item }
+    "#;
+    let result = parse(source);
+
+    let StatementKind::If(if_statement) = &result.statements[0].kind else {
+        panic!("expected an if statement");
+    };
+    assert_eq!(identifier_name(&if_statement.condition), "a");
+    let Some(Else::If(else_if)) = &if_statement.else_branch else {
+        panic!("expected an else if");
+    };
+    let Some(Else::Block(else_block)) = &else_if.else_branch else {
+        panic!("expected an else block");
+    };
+    assert_eq!(else_block.statements[0].kind, StatementKind::Return(None));
+
+    assert!(matches!(
+        &result.statements[1].kind,
+        StatementKind::While { body, .. } if body.statements.len() == 1
+    ));
+    let StatementKind::For {
+        variable, iterable, ..
+    } = &result.statements[2].kind
+    else {
+        panic!("expected a for loop");
+    };
+    assert_eq!(variable.name, "item");
+    assert_eq!(identifier_name(iterable), "items");
+}
+
+#[test]
+fn parses_trailing_closures() {
+    let cases = [
+        (
+            "arr.first { $0 < 10 && $0 > 5 }",
+            "arr.first() { (($0 LessThan 10) And ($0 GreaterThan 5)) }",
+        ),
+        ("f(x) { $0 }", "f(x) { $0 }"),
+        ("f(x) { $0 } { $1 }", "f(x) { $0 }() { $1 }"),
+        (
+            "a.map { x in x * 2 }.count",
+            "a.map() { x in (x Multiply 2) }.count",
+        ),
+        ("f { g { $0 } }", "f() { g() { $0 } }"),
+        ("f {}", "f() {}"),
+    ];
+
+    for (source, expected) in cases {
+        assert_eq!(parenthesize(source), expected, "{}", source);
+    }
+}
+
+#[test]
+fn parses_closures() {
+    let cases = [
+        ("{ $0 + $1 }", "{ ($0 Add $1) }"),
+        ("{ x, y in x + y }", "{ x, y in (x Add y) }"),
+        ("{ () in 1 }", "{ in 1 }"),
+        ("{ x in\n    print(x)\n    x\n}", "{ x in print(x); x }"),
+        ("{ (x: i32, y) in x }", "{ x, y in x }"),
+        ("{ a < b }", "{ (a LessThan b) }"),
+        ("{}", "{}"),
+    ];
+
+    for (source, expected) in cases {
+        assert_eq!(parenthesize(source), expected, "{}", source);
+    }
+}
+
+#[test]
+fn parses_closure_signatures() {
+    let source = "{ (x: i32, y) async throws -> i32[] in x + y }";
+    let result = parse(source);
+
+    let ExpressionKind::Closure(closure) = expression(&result.statements[0]) else {
+        panic!("expected a closure");
+    };
+    let Some(signature) = &closure.signature else {
+        panic!("expected a signature");
+    };
+    let parameters: Vec<_> = signature
+        .parameters
+        .iter()
+        .map(|parameter| {
+            (
+                parameter.name.name.as_ref(),
+                parameter.type_annotation.as_ref().map(write_type),
+            )
+        })
+        .collect();
+    assert_eq!(
+        parameters,
+        vec![("x", Some("i32".to_string())), ("y", None)]
+    );
+    assert!(signature.is_async);
+    assert!(signature.is_throwing);
+    assert_eq!(
+        signature.return_type.as_ref().map(write_type),
+        Some("(i32)[]".to_string())
+    );
+    assert_eq!(
+        signature.location,
+        SourceLocation::new(1, 3, 2)..SourceLocation::new(1, 36, 35)
+    );
+    assert!(matches!(
+        closure.body[..],
+        [Statement {
+            kind: StatementKind::ImplicitReturn(_),
+            ..
+        }]
+    ));
+    assert_eq!(
+        closure.location,
+        SourceLocation::new(1, 1, 0)..SourceLocation::new(1, 47, 46)
+    );
+}
+
+#[test]
+fn reports_closure_signatures_without_parameters() {
+    let source = "{ in 1 }";
+    let (_, errors) = parse_with_errors(source);
+
+    assert_eq!(
+        errors[0],
+        ParserError::new(
+            SyntaxError::ExpectedToken {
+                expected: Expected::Identifier,
+                found: Keyword::In.into(),
+            },
+            SourceLocation::new(1, 3, 2),
+            SourceLocation::new(1, 5, 4),
+        )
+    );
+}
+
+#[test]
+fn parses_braces_after_conditions_as_bodies() {
+    let source = r#"
+        if a.b { c }
+        while f(x) { step() }
+        for item in items.sorted() { print(item) }
+        if a.contains(where: { $0 > 1 }) {}
+        if (a.first { $0 > 5 }) != nil {}
+    "#;
+    let result = parse(source);
+
+    assert_eq!(result.statements.len(), 5);
+    let conditions: Vec<_> = result
+        .statements
+        .iter()
+        .map(|statement| match &statement.kind {
+            StatementKind::If(if_statement) => &if_statement.condition,
+            StatementKind::While { condition, .. } => condition,
+            StatementKind::For { iterable, .. } => iterable,
+            kind => panic!("expected a condition, got {:?}", kind),
+        })
+        .map(write_parenthesized)
+        .collect();
+    assert_eq!(
+        conditions,
+        vec![
+            "a.b",
+            "f(x)",
+            "items.sorted()",
+            "a.contains({ ($0 GreaterThan 1) })",
+            "(a.first() { ($0 GreaterThan 5) } NotEq nil)",
+        ]
+    );
+}
+
+#[test]
+fn reports_trailing_closure_in_condition() {
+    let source = "if a.map { $0 } {}";
+    let (_, errors) = parse_with_errors(source);
+
+    assert_eq!(
+        errors[0],
+        ParserError::new(
+            SyntaxError::ExpectedToken {
+                expected: Expected::StatementEnd,
+                found: TokenKind::OpenBrace,
+            },
+            SourceLocation::new(1, 17, 16),
+            SourceLocation::new(1, 18, 17),
+        )
+    );
+}
+
+#[test]
+fn parses_brace_on_next_line_as_new_statement() {
+    let source = r#"
+        f(x)
+        { $0 }
+    "#;
+    let result = parse(source);
+
+    assert_eq!(result.statements.len(), 2);
+    assert!(matches!(
+        expression(&result.statements[0]),
+        ExpressionKind::Call {
+            trailing_closure: None,
+            ..
+        }
+    ));
+    assert!(matches!(
+        expression(&result.statements[1]),
+        ExpressionKind::Closure(_)
+    ));
+}
+
+#[test]
+fn parses_example() {
+    parse(include_str!("../../examples/example.shab"));
+}
+
+#[test]
+fn parses_contextual_keywords_as_identifiers() {
+    let source = "let async = throws";
+    let result = parse(source);
+
+    let DeclarationKind::Variable(variable) = &declaration(&result.statements[0]).kind else {
+        panic!("expected a variable");
+    };
+    assert_eq!(variable.name.name, "async");
+}
+
+#[test]
+fn reports_unclosed_block() {
+    let source = "fn f() { g()";
+    let (program, errors) = parse_with_errors(source);
+
+    let expected = vec![ParserError::new(
+        SyntaxError::UnclosedDelimiter {
+            delimiter: TokenKind::OpenBrace,
+            opening: SourceLocation::new(1, 8, 7)..SourceLocation::new(1, 9, 8),
+        },
+        SourceLocation::new(1, 13, 12),
+        SourceLocation::new(1, 13, 12),
+    )];
+
+    assert_eq!(errors, expected);
+    let DeclarationKind::Function(function) = &declaration(&program.statements[0]).kind else {
+        panic!("expected a function");
+    };
+    assert_eq!(function.body.statements.len(), 1);
+}
+
+#[test]
+fn reports_unclosed_delimiters() {
+    let source = r#"
+        f(a, b
+        let c = [1, 2)
+        fn f() {
+            g(
+        }
+        h(
+        let d = 1
+    "#;
+    let (program, errors) = parse_with_errors(source);
+
+    let expected = vec![
+        ParserError::new(
+            SyntaxError::UnclosedDelimiter {
+                delimiter: TokenKind::OpenParen,
+                opening: SourceLocation::new(2, 10, 10)..SourceLocation::new(2, 11, 11),
+            },
+            SourceLocation::new(3, 9, 24),
+            SourceLocation::new(3, 9, 24),
+        ),
+        ParserError::new(
+            SyntaxError::UnclosedDelimiter {
+                delimiter: TokenKind::OpenBracket,
+                opening: SourceLocation::new(3, 17, 32)..SourceLocation::new(3, 18, 33),
+            },
+            SourceLocation::new(3, 22, 37),
+            SourceLocation::new(3, 22, 37),
+        ),
+        ParserError::new(
+            SyntaxError::UnclosedDelimiter {
+                delimiter: TokenKind::OpenParen,
+                opening: SourceLocation::new(5, 14, 69)..SourceLocation::new(5, 15, 70),
+            },
+            SourceLocation::new(6, 9, 79),
+            SourceLocation::new(6, 9, 79),
+        ),
+        ParserError::new(
+            SyntaxError::UnclosedDelimiter {
+                delimiter: TokenKind::OpenParen,
+                opening: SourceLocation::new(7, 10, 90)..SourceLocation::new(7, 11, 91),
+            },
+            SourceLocation::new(8, 9, 100),
+            SourceLocation::new(8, 9, 100),
+        ),
+    ];
+
+    assert_eq!(errors, expected);
+    assert_eq!(program.statements.len(), 2);
+}
+
+#[test]
+fn reports_unexpected_eof() {
+    let source = "let a =";
+    let (_, errors) = parse_with_errors(source);
+
+    let expected = vec![ParserError::new(
+        SyntaxError::UnexpectedEof {
+            expected: Expected::Expression,
+        },
+        SourceLocation::new(1, 8, 7),
+        SourceLocation::new(1, 8, 7),
+    )];
+
+    assert_eq!(errors, expected);
+}
+
+#[test]
+fn reports_invalid_assignment_targets() {
+    let source = r#"
+        f() = 1
+        a.b[0] += 1
+        -a = 2
+    "#;
+    let (program, errors) = parse_with_errors(source);
+
+    let expected = vec![
+        ParserError::new(
+            SyntaxError::InvalidAssignmentTarget,
+            SourceLocation::new(2, 9, 9),
+            SourceLocation::new(2, 12, 12),
+        ),
+        ParserError::new(
+            SyntaxError::InvalidAssignmentTarget,
+            SourceLocation::new(4, 9, 45),
+            SourceLocation::new(4, 11, 47),
+        ),
+    ];
+
+    assert_eq!(errors, expected);
+    assert_eq!(program.statements.len(), 3);
+}
+
+#[test]
+fn recovers_at_statements_and_declarations() {
+    let source = r#"
+        fn f() {
+            let = 1
+            g()
+        }
+        ) }
+        struct S {
+            h()
+            let a: i32
+        }
+        let b = 2
+    "#;
+    let (program, errors) = parse_with_errors(source);
+
+    let errors: Vec<_> = errors.iter().map(ParserError::error).collect();
+    let expected = [
+        SyntaxError::ExpectedToken {
+            expected: Expected::Identifier,
+            found: TokenKind::Eq,
+        },
+        SyntaxError::ExpectedToken {
+            expected: Expected::Expression,
+            found: TokenKind::CloseParen,
+        },
+        SyntaxError::ExpectedToken {
+            expected: Expected::Expression,
+            found: TokenKind::CloseBrace,
+        },
+        SyntaxError::ExpectedToken {
+            expected: Expected::Declaration,
+            found: TokenKind::Identifier("h".into()),
+        },
+    ];
+    assert_eq!(errors, expected.iter().collect::<Vec<_>>());
+
+    assert_eq!(program.statements.len(), 3);
+    let DeclarationKind::Function(function) = &declaration(&program.statements[0]).kind else {
+        panic!("expected a function");
+    };
+    assert!(matches!(
+        &function.body.statements[..],
+        [Statement {
+            kind: StatementKind::ImplicitReturn(_),
+            ..
+        }]
+    ));
+    let DeclarationKind::Struct(s) = &declaration(&program.statements[1]).kind else {
+        panic!("expected a struct");
+    };
+    assert_eq!(s.members.len(), 1);
+}
+
+#[test]
+fn skips_braces_in_pairs_when_recovering() {
+    let source = r#"
+        let a = ) { if b {} }
+        let c = 1
+    "#;
+    let (program, errors) = parse_with_errors(source);
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(program.statements.len(), 1);
+}
+
+#[test]
+fn reports_lexer_errors_in_source_order() {
+    let source = r#"
+        let a = €
+        let b = )
+        let c = "abc
+    "#;
+    let (program, errors) = parse_with_errors(source);
+
+    let expected = vec![
+        LexerError::new(
+            TokenizeError::UnknownLexme('€'),
+            SourceLocation::new(2, 17, 17),
+            SourceLocation::new(2, 18, 20),
+        )
+        .into(),
+        ParserError::new(
+            SyntaxError::ExpectedToken {
+                expected: Expected::Expression,
+                found: TokenKind::CloseParen,
+            },
+            SourceLocation::new(3, 17, 37),
+            SourceLocation::new(3, 18, 38),
+        ),
+        LexerError::new(
+            TokenizeError::UnterminatedString,
+            SourceLocation::new(4, 17, 55),
+            SourceLocation::new(4, 21, 59),
+        )
+        .into(),
+    ];
+
+    assert_eq!(errors, expected);
+    assert!(program.statements.is_empty());
+}
+
+#[test]
+fn orders_precedence_from_loosest_to_tightest() {
+    let table = [
+        (TokenKind::Eq, Precedence::Assignment),
+        (TokenKind::OrOr, Precedence::Disjunction),
+        (TokenKind::AndAnd, Precedence::Conjunction),
+        (TokenKind::LessThan, Precedence::Comparison),
+        (TokenKind::NilCoalescing, Precedence::NilCoalescing),
+        (TokenKind::HalfOpenRange, Precedence::Range),
+        (TokenKind::Plus, Precedence::Addition),
+        (TokenKind::Asterisk, Precedence::Multiplication),
+        (TokenKind::ShiftLeft, Precedence::Shift),
+    ];
+    for (kind, precedence) in &table {
+        assert_eq!(Precedence::of(kind, Fixity::Infix), Some(*precedence));
+    }
+    for pair in table.windows(2) {
+        assert!(pair[0].1 < pair[1].1);
+    }
+
+    assert!(Precedence::Shift < Precedence::Prefix);
+    assert!(Precedence::Prefix < Precedence::Postfix);
+    assert_eq!(
+        Precedence::of(&TokenKind::Negate, Fixity::Prefix),
+        Some(Precedence::Prefix)
+    );
+    assert_eq!(
+        Precedence::of(&TokenKind::OpenBracket, Fixity::Postfix),
+        Some(Precedence::Postfix)
+    );
+    assert_eq!(Precedence::of(&TokenKind::Negate, Fixity::Infix), None);
+    assert_eq!(Precedence::of(&TokenKind::Comma, Fixity::Infix), None);
+}
+
+#[test]
+fn matches_operators_to_precedence_and_associativity() {
+    let operators = [
+        ("=", Precedence::Assignment, Associativity::Right),
+        ("%=", Precedence::Assignment, Associativity::Right),
+        ("||", Precedence::Disjunction, Associativity::Left),
+        ("&&", Precedence::Conjunction, Associativity::Left),
+        ("==", Precedence::Comparison, Associativity::None),
+        ("!=", Precedence::Comparison, Associativity::None),
+        (">=", Precedence::Comparison, Associativity::None),
+        ("??", Precedence::NilCoalescing, Associativity::Right),
+        ("...", Precedence::Range, Associativity::None),
+        ("..=", Precedence::Range, Associativity::None),
+        ("-", Precedence::Addition, Associativity::Left),
+        ("|", Precedence::Addition, Associativity::Left),
+        ("^", Precedence::Addition, Associativity::Left),
+        ("%", Precedence::Multiplication, Associativity::Left),
+        ("&", Precedence::Multiplication, Associativity::Left),
+        (">>", Precedence::Shift, Associativity::None),
+    ];
+
+    for (operator, precedence, associativity) in operators {
+        let kind = TokenKind::from_operator(operator).unwrap();
+        assert_eq!(
+            Precedence::of(&kind, Fixity::Infix),
+            Some(precedence),
+            "{}",
+            operator
+        );
+        assert_eq!(precedence.associativity(), associativity, "{}", operator);
+    }
+}
+
+#[test]
+fn parses_binary_operators_by_precedence() {
+    let cases = [
+        ("1 + 2 * 3", "(1 Add (2 Multiply 3))"),
+        ("1 * 2 + 3", "((1 Multiply 2) Add 3)"),
+        ("1 - 2 - 3", "((1 Subtract 2) Subtract 3)"),
+        (
+            "$0 < 10 && $0 > 5",
+            "(($0 LessThan 10) And ($0 GreaterThan 5))",
+        ),
+        ("a || b && c", "(a Or (b And c))"),
+        ("a ?? b ?? c", "(a NilCoalescing (b NilCoalescing c))"),
+        ("a ?? 0 > 5", "((a NilCoalescing 0) GreaterThan 5)"),
+        ("0..<n + 1", "(0 HalfOpenRange (n Add 1))"),
+        ("a | b & c", "(a BitwiseOr (b BitwiseAnd c))"),
+        ("1 << 2 * 3", "((1 ShiftLeft 2) Multiply 3)"),
+        ("(1 + 2) * 3", "((1 Add 2) Multiply 3)"),
+        ("a = b += 1 + 2", "(a Assign= (b Add= (1 Add 2)))"),
+    ];
+
+    for (source, expected) in cases {
+        assert_eq!(parenthesize(source), expected, "{}", source);
+    }
+}
+
+#[test]
+fn parses_prefix_and_postfix_operators() {
+    let cases = [
+        ("-a * b", "((-a) Multiply b)"),
+        ("!a && b", "((!a) And b)"),
+        ("- -a", "(-(-a))"),
+        ("~a.b", "(~a.b)"),
+        ("-a[0]", "(-a[0])"),
+        ("-f(x)", "(-f(x))"),
+        ("a.b(c)[d].e", "a.b(c)[d].e"),
+        ("a?.b", "a?.b"),
+        ("a?[0]?.b()", "a?[0]?.b()"),
+        ("a? ?? b", "(a? NilCoalescing b)"),
+        (
+            r#"self.name + " is " + self.age.description"#,
+            "((self.name Add \" is \") Add self.age.description)",
+        ),
+    ];
+
+    for (source, expected) in cases {
+        assert_eq!(parenthesize(source), expected, "{}", source);
+    }
+}
+
+#[test]
+fn fails_on_chained_non_associative_operators() {
+    let sources = [
+        ("a < b < c", TokenKind::LessThan),
+        ("a == b != c", TokenKind::NotEq),
+        ("0..<1...2", TokenKind::Ellipsis),
+        ("1 << 2 >> 3", TokenKind::ShiftRight),
+    ];
+    for (source, operator) in sources {
+        let (_, errors) = parse_with_errors(source);
+        let errors: Vec<_> = errors.iter().map(ParserError::error).collect();
+        let expected = SyntaxError::ChainedNonAssociativeOperator(operator);
+        assert_eq!(errors, vec![&expected], "{}", source);
+    }
+
+    assert_eq!(parenthesize("(a < b) == c"), "((a LessThan b) Eq c)");
+}
+
+#[test]
+fn continues_expressions_on_lines_starting_with_an_infix_operator() {
+    let source = r#"
+        let isValid = a
+            && b
+        -c
+        d
+            [0]
+    "#;
+    let result = parse(source);
+
+    assert_eq!(result.statements.len(), 4);
+    assert!(matches!(
+        &declaration(&result.statements[0]).kind,
+        DeclarationKind::Variable(VariableDeclaration {
+            initializer: Some(Initializer::Value(Expression {
+                kind: ExpressionKind::Binary {
+                    operator: BinaryOperator::And,
+                    ..
+                },
+                ..
+            })),
+            ..
+        })
+    ));
+    assert!(matches!(
+        expression(&result.statements[1]),
+        ExpressionKind::Unary {
+            operator: UnaryOperator::Negate,
+            ..
+        }
+    ));
+    assert!(matches!(
+        expression(&result.statements[3]),
+        ExpressionKind::Array(_)
+    ));
+}
+
+#[test]
+fn parses_optional_chain_locations() {
+    let source = "a?.b";
+    let result = parse(source);
+
+    let ExpressionKind::MemberAccess { object, .. } = expression(&result.statements[0]) else {
+        panic!("expected a member access");
+    };
+    assert_eq!(
+        object.location,
+        SourceLocation::new(1, 1, 0)..SourceLocation::new(1, 3, 2)
+    );
+    assert_eq!(
+        result.statements[0].location,
+        SourceLocation::new(1, 1, 0)..SourceLocation::new(1, 5, 4)
+    );
+}
+
+#[test]
+fn fails_on_detached_question_mark() {
+    let source = "a ?";
+    let (_, errors) = parse_with_errors(source);
+
+    let expected = vec![ParserError::new(
+        SyntaxError::ExpectedToken {
+            expected: Expected::StatementEnd,
+            found: TokenKind::QuestionMark,
+        },
+        SourceLocation::new(1, 3, 2),
+        SourceLocation::new(1, 4, 3),
+    )];
+
+    assert_eq!(errors, expected);
+}
+
+#[test]
+fn parses_compound_assignment() {
+    let source = "count -= 1";
+    let result = parse(source);
+
+    assert!(matches!(
+        expression(&result.statements[0]),
+        ExpressionKind::Assignment {
+            operator: AssignmentOperator::Subtract,
+            ..
+        }
+    ));
+}
+
 #[test]
-fn it_adds_two() {
-    assert_eq!(4, 4);
+fn recovers_from_generated_invalid_sources() {
+    let fragments = [
+        "let",
+        "var",
+        "fn",
+        "struct",
+        "extend",
+        "if",
+        "else",
+        "for",
+        "in",
+        "return",
+        "a",
+        "1",
+        "(",
+        ")",
+        "[",
+        "]",
+        "{",
+        "}",
+        ",",
+        ";",
+        ":",
+        "=",
+        "-",
+        "<",
+        "?",
+        "?.",
+        ".",
+        "->",
+        "@x",
+        "/// docs\n",
+        "\"a \\(b) c\"",
+        "\"\\(",
+        "\"open",
+        "$0",
+        "€",
+    ];
+    let separators = [" ", "", "\n"];
+
+    for source in generate_sources(&fragments, &separators, 2000) {
+        let (_, errors) = parse_with_errors(&source);
+        for error in errors {
+            assert!(error.location().end.offset() <= source.len(), "{}", source);
+        }
+    }
 }